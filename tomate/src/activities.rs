@@ -0,0 +1,195 @@
+use std::{
+    process,
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use serde::Deserialize;
+
+use crate::i18n::t;
+
+/// Something to do during a break, picked more often the heavier it is.
+#[derive(Clone, Deserialize)]
+pub struct Activity {
+    pub name: String,
+    #[serde(default = "default_weight")]
+    pub weight: u32,
+}
+
+fn default_weight() -> u32 {
+    1
+}
+
+/// The `[break_activities]` table of the config file.
+#[derive(Default, Deserialize)]
+#[serde(default)]
+pub struct ActivityConfig {
+    pub enabled: bool,
+    /// Whether to ask at the end of each break if the activity was done, `true` by default.
+    pub ask: Option<bool>,
+    /// The activities to pick from, a few from the language catalog when empty.
+    pub list: Vec<Activity>,
+}
+
+impl ActivityConfig {
+    /// Whether every activity can be picked.
+    pub fn is_valid(&self) -> bool {
+        self.list.iter().all(|activity| activity.weight > 0)
+    }
+}
+
+/// Suggests an activity for each break, never the same twice in a sprint until all of them were suggested.
+pub struct ActivityPicker {
+    activities: Vec<Activity>,
+    suggested: Vec<bool>,
+    /// The last one suggested, not suggested again right away when starting over.
+    last: Option<usize>,
+    pub ask: bool,
+    /// State of a xorshift generator, no need for anything better to pick a stretch.
+    state: u64,
+}
+
+impl ActivityPicker {
+    /// `None` unless enabled in the config.
+    pub fn new(config: ActivityConfig) -> Option<Self> {
+        if !config.enabled {
+            return None;
+        }
+
+        let activities = if config.list.is_empty() {
+            t().default_activities
+                .iter()
+                .map(|name| Activity {
+                    name: (*name).to_owned(),
+                    weight: default_weight(),
+                })
+                .collect()
+        } else {
+            config.list
+        };
+
+        let seed = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map_or(0, |now| now.as_nanos() as u64)
+            ^ u64::from(process::id());
+
+        Some(Self {
+            suggested: vec![false; activities.len()],
+            last: None,
+            activities,
+            ask: config.ask.unwrap_or(true),
+            // Zero would stay zero forever
+            state: seed | 1,
+        })
+    }
+
+    /// A weighted pick among the activities not suggested yet, starting over once all of them were, but the last one.
+    pub fn pick(&mut self) -> String {
+        if self.suggested.iter().all(|&suggested| suggested) {
+            self.suggested.fill(false);
+
+            if let (Some(last), 2..) = (self.last, self.activities.len()) {
+                self.suggested[last] = true;
+            }
+        }
+
+        let roll = self.next();
+        let available = || {
+            self.activities
+                .iter()
+                .zip(&self.suggested)
+                .enumerate()
+                .filter(|(_, (_, &suggested))| !suggested)
+                .map(|(index, (activity, _))| (index, activity.weight as u64))
+        };
+
+        let total: u64 = available().map(|(_, weight)| weight).sum();
+        let mut target = roll % total;
+
+        let index = available()
+            .find(|&(_, weight)| {
+                let found = target < weight;
+                target = target.saturating_sub(weight);
+                found
+            })
+            .map(|(index, _)| index)
+            .unwrap();
+
+        self.suggested[index] = true;
+        self.last = Some(index);
+        self.activities[index].name.clone()
+    }
+
+    fn next(&mut self) -> u64 {
+        self.state ^= self.state << 13;
+        self.state ^= self.state >> 7;
+        self.state ^= self.state << 17;
+        self.state
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Activities named after their index, with the given weights.
+    fn picker(weights: &[u32], seed: u64) -> ActivityPicker {
+        let activities: Vec<_> = weights
+            .iter()
+            .enumerate()
+            .map(|(index, &weight)| Activity {
+                name: index.to_string(),
+                weight,
+            })
+            .collect();
+
+        ActivityPicker {
+            suggested: vec![false; activities.len()],
+            last: None,
+            activities,
+            ask: true,
+            state: seed | 1,
+        }
+    }
+
+    fn picks(picker: &mut ActivityPicker, count: usize) -> Vec<String> {
+        (0..count).map(|_| picker.pick()).collect()
+    }
+
+    #[test]
+    fn all_of_them_before_any_again() {
+        for seed in 0..100 {
+            let mut picker = picker(&[1, 2, 3, 4], seed);
+
+            let mut round = picks(&mut picker, 4);
+            round.sort();
+            assert_eq!(round, ["0", "1", "2", "3"], "seed {seed}");
+        }
+    }
+
+    #[test]
+    fn never_the_same_twice_in_a_row() {
+        for seed in 0..100 {
+            let mut picker = picker(&[1, 5, 1], seed);
+            let picks = picks(&mut picker, 30);
+
+            assert!(
+                picks.windows(2).all(|pair| pair[0] != pair[1]),
+                "seed {seed}: {picks:?}"
+            );
+        }
+    }
+
+    #[test]
+    fn heavier_ones_first_more_often() {
+        let heavy = (0..1000).filter(|&seed| picker(&[1, 9], seed).pick() == "1").count();
+
+        assert!((850..=950).contains(&heavy), "{heavy} of 1000");
+    }
+
+    #[test]
+    fn a_single_activity_every_time() {
+        let mut picker = picker(&[3], 7);
+
+        assert_eq!(picks(&mut picker, 3), ["0", "0", "0"]);
+    }
+}
@@ -0,0 +1,226 @@
+use std::time::Duration;
+
+use serde::Deserialize;
+
+use crate::{
+    history::TimerStats,
+    i18n::{render, t},
+    time::{describe, HumanDuration, MINUTE},
+};
+
+const DEFAULT_MIN: Duration = Duration::from_secs(3 * 60);
+const DEFAULT_MAX: Duration = Duration::from_secs(15 * 60);
+const DEFAULT_STREAK_BONUS: Duration = MINUTE;
+const DEFAULT_SKIP_PENALTY: Duration = Duration::from_secs(2 * 60);
+
+/// The `[adaptive_rest]` table of the config file, also enabled by `--adaptive-rest`.
+#[derive(Default, Deserialize)]
+#[serde(default)]
+pub struct AdaptiveRestConfig {
+    pub enabled: bool,
+    /// The shortest a break can get, 3 minutes by default.
+    pub min: Option<HumanDuration>,
+    /// The longest a break can get, 15 minutes by default.
+    pub max: Option<HumanDuration>,
+    /// Added for every tomato in a row without pauses, after the first.
+    pub streak_bonus: Option<HumanDuration>,
+    /// Taken off after a skipped tomato.
+    pub skip_penalty: Option<HumanDuration>,
+}
+
+impl AdaptiveRestConfig {
+    fn min(&self) -> Duration {
+        self.min.map_or(DEFAULT_MIN, |min| min.0)
+    }
+
+    fn max(&self) -> Duration {
+        self.max.map_or(DEFAULT_MAX, |max| max.0)
+    }
+
+    /// Whether the bounds are in order.
+    pub fn is_valid(&self) -> bool {
+        self.min() <= self.max()
+    }
+}
+
+/// Picks the length of each short break from how the tomatoes before it went, long breaks are
+/// left as planned.
+pub struct AdaptiveRest {
+    min: Duration,
+    max: Duration,
+    streak_bonus: Duration,
+    skip_penalty: Duration,
+    /// Tomatoes in a row finished without pauses.
+    streak: u32,
+    skipped: bool,
+}
+
+impl AdaptiveRest {
+    pub fn new(config: &AdaptiveRestConfig) -> Self {
+        Self {
+            min: config.min(),
+            max: config.max(),
+            streak_bonus: config.streak_bonus.map_or(DEFAULT_STREAK_BONUS, |bonus| bonus.0),
+            skip_penalty: config.skip_penalty.map_or(DEFAULT_SKIP_PENALTY, |penalty| penalty.0),
+            streak: 0,
+            skipped: false,
+        }
+    }
+
+    /// Takes note of how a tomato went, before adjusting the break after it.
+    pub fn record(&mut self, stats: &TimerStats) {
+        self.skipped = stats.skipped;
+        self.streak = if stats.skipped || stats.pauses > 0 {
+            0
+        } else {
+            self.streak + 1
+        };
+    }
+
+    /// The length of the next break, from the planned one and what the overtime added to it,
+    /// with the reasons for it.
+    pub fn adjust(&self, planned: Duration, overtime_extra: Duration) -> (Duration, String) {
+        let mut rest = planned + overtime_extra;
+        let mut reasons = Vec::new();
+
+        if self.streak > 1 {
+            let bonus = self.streak_bonus * (self.streak - 1);
+            rest += bonus;
+            reasons.push(render(
+                t().adaptive_streak,
+                &[("count", &self.streak), ("time", &describe(bonus))],
+            ));
+        }
+        if !overtime_extra.is_zero() {
+            reasons.push(render(t().adaptive_overtime, &[("time", &describe(overtime_extra))]));
+        }
+        if self.skipped {
+            let penalty = self.skip_penalty.min(rest);
+            rest -= penalty;
+            reasons.push(render(t().adaptive_skipped, &[("time", &describe(penalty))]));
+        }
+
+        if rest < self.min {
+            rest = self.min;
+            reasons.push(render(t().adaptive_at_least, &[("time", &describe(self.min))]));
+        } else if rest > self.max {
+            rest = self.max;
+            reasons.push(render(t().adaptive_at_most, &[("time", &describe(self.max))]));
+        }
+
+        if reasons.is_empty() {
+            reasons.push(t().adaptive_steady.to_owned());
+        }
+
+        (rest, reasons.join(", "))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn adaptive_rest() -> AdaptiveRest {
+        AdaptiveRest::new(&AdaptiveRestConfig {
+            enabled: true,
+            ..AdaptiveRestConfig::default()
+        })
+    }
+
+    fn tomato(pauses: u32, skipped: bool) -> TimerStats {
+        TimerStats {
+            pauses,
+            skipped,
+            ..TimerStats::default()
+        }
+    }
+
+    #[test]
+    fn as_planned_after_a_single_tomato() {
+        let mut rest = adaptive_rest();
+        rest.record(&tomato(0, false));
+
+        assert_eq!(
+            rest.adjust(5 * MINUTE, Duration::ZERO),
+            (5 * MINUTE, t().adaptive_steady.to_owned())
+        );
+    }
+
+    #[test]
+    fn longer_after_a_streak() {
+        let mut rest = adaptive_rest();
+        for _ in 0..3 {
+            rest.record(&tomato(0, false));
+        }
+        assert_eq!(rest.adjust(5 * MINUTE, Duration::ZERO).0, 7 * MINUTE);
+
+        // A pause ends the streak
+        rest.record(&tomato(1, false));
+        assert_eq!(rest.adjust(5 * MINUTE, Duration::ZERO).0, 5 * MINUTE);
+    }
+
+    #[test]
+    fn shorter_after_a_skipped_tomato() {
+        let mut rest = adaptive_rest();
+        rest.record(&tomato(0, true));
+
+        let (duration, reasons) = rest.adjust(6 * MINUTE, Duration::ZERO);
+        assert_eq!(duration, 4 * MINUTE);
+        assert_eq!(
+            reasons,
+            render(t().adaptive_skipped, &[("time", &describe(2 * MINUTE))])
+        );
+    }
+
+    #[test]
+    fn clamped_to_the_minimum() {
+        let mut rest = adaptive_rest();
+        rest.record(&tomato(0, true));
+
+        let (duration, reasons) = rest.adjust(4 * MINUTE, Duration::ZERO);
+        assert_eq!(duration, DEFAULT_MIN);
+        assert!(reasons.ends_with(&render(t().adaptive_at_least, &[("time", &describe(DEFAULT_MIN))])));
+
+        // Even when planned shorter than it, the penalty never going past zero
+        assert_eq!(rest.adjust(MINUTE, Duration::ZERO).0, DEFAULT_MIN);
+    }
+
+    #[test]
+    fn clamped_to_the_maximum() {
+        let mut rest = adaptive_rest();
+        for _ in 0..5 {
+            rest.record(&tomato(0, false));
+        }
+
+        // The overtime counts towards it too
+        let (duration, reasons) = rest.adjust(5 * MINUTE, 8 * MINUTE);
+        assert_eq!(duration, DEFAULT_MAX);
+        assert!(reasons.ends_with(&render(t().adaptive_at_most, &[("time", &describe(DEFAULT_MAX))])));
+    }
+
+    #[test]
+    fn configured_bounds() {
+        let mut rest = AdaptiveRest::new(&AdaptiveRestConfig {
+            enabled: true,
+            min: Some(HumanDuration(4 * MINUTE)),
+            max: Some(HumanDuration(6 * MINUTE)),
+            streak_bonus: Some(HumanDuration(2 * MINUTE)),
+            skip_penalty: Some(HumanDuration(3 * MINUTE)),
+        });
+
+        rest.record(&tomato(0, false));
+        rest.record(&tomato(0, false));
+        assert_eq!(rest.adjust(5 * MINUTE, Duration::ZERO).0, 6 * MINUTE);
+
+        rest.record(&tomato(0, true));
+        assert_eq!(rest.adjust(5 * MINUTE, Duration::ZERO).0, 4 * MINUTE);
+
+        // Bounds out of order are caught when loading the config
+        let config = AdaptiveRestConfig {
+            min: Some(HumanDuration(10 * MINUTE)),
+            max: Some(HumanDuration(5 * MINUTE)),
+            ..AdaptiveRestConfig::default()
+        };
+        assert!(!config.is_valid());
+    }
+}
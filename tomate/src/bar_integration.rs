@@ -1,13 +1,18 @@
 use std::io;
 
-use crate::{Stage, Time};
+use crate::{plan::Progress, Stage, Time};
 
 #[allow(unused)]
 const CLIMSG_CHANNEL: &str = "tomate-pomodoro";
 
-pub enum BarMessage {
-    Running(Time, Stage),
-    Paused(Time, Stage),
+/// The time, stage and progress of the timer, with the task and the progress towards the goals, if any.
+// Only read when sent to the bar
+#[cfg_attr(not(feature = "bar-integration"), allow(dead_code))]
+pub enum BarMessage<'a> {
+    Running(Time, Stage, Progress, Option<&'a str>, Option<&'a str>),
+    Paused(Time, Stage, Progress, Option<&'a str>, Option<&'a str>),
+    /// Counting up after the tomato ended, see the overtime mode.
+    Overtime(Time, Stage, Progress, Option<&'a str>, Option<&'a str>),
     Disconnecting,
 }
 
@@ -15,6 +20,8 @@ cfg_if::cfg_if! {
     if #[cfg(feature = "bar-integration")] {
         use climsg_core::{ClientMessage, MessageStream};
 
+        use crate::i18n::t;
+
         pub struct BarMessager {
             stream: MessageStream,
         }
@@ -26,10 +33,15 @@ cfg_if::cfg_if! {
 
             pub fn send_message(&mut self, message: BarMessage) -> climsg_core::Result<()> {
                 let msg = match message {
-                    BarMessage::Running(time, Stage::Work) => format!(" work - {time} "),
-                    BarMessage::Running(time, Stage::Rest) => format!(" rest - {time} "),
-                    BarMessage::Paused(time, Stage::Work) => format!(" work - {time} (Paused) "),
-                    BarMessage::Paused(time, Stage::Rest) => format!(" rest - {time} (Paused) "),
+                    BarMessage::Running(time, stage, progress, task, goal) => {
+                        format!(" {} - {time} ({progress}){}{} ", name(stage), label(task), goals(goal))
+                    }
+                    BarMessage::Paused(time, stage, progress, task, goal) => {
+                        format!(" {} - {time} ({progress}){}{} {} ", name(stage), label(task), goals(goal), t().paused)
+                    }
+                    BarMessage::Overtime(time, stage, progress, task, goal) => {
+                        format!(" {} - +{time} ({progress}){}{} ", name(stage), label(task), goals(goal))
+                    }
                     BarMessage::Disconnecting => String::new(),
                 };
 
@@ -42,6 +54,21 @@ cfg_if::cfg_if! {
                 }
             }
         }
+
+        fn name(stage: Stage) -> &'static str {
+            match stage {
+                Stage::Work => t().bar_work,
+                Stage::Rest => t().bar_rest,
+            }
+        }
+
+        fn label(task: Option<&str>) -> String {
+            task.map(|task| format!(" [{task}]")).unwrap_or_default()
+        }
+
+        fn goals(goal: Option<&str>) -> String {
+            goal.map(|goal| format!(" · {goal}")).unwrap_or_default()
+        }
     } else {
         pub struct BarMessager;
 
@@ -0,0 +1,313 @@
+use std::{fs, io, path::PathBuf};
+
+use chrono::{DateTime, Local, Utc};
+use chrono_tz::Tz;
+use clap::Args;
+
+use crate::{
+    error::{OrError, Result, TomateError},
+    history::{self, DateRange, Record},
+    i18n::{render, t},
+    time::Time,
+    Stage,
+};
+
+/// Content lines longer than this many bytes are folded, as required by RFC 5545.
+const MAX_LINE_BYTES: usize = 75;
+
+#[derive(Args)]
+pub struct ExportArgs {
+    /// Export the recorded tomatoes and breaks as iCalendar events.
+    #[arg(long, required = true)]
+    ics: bool,
+    #[command(flatten)]
+    range: DateRange,
+    /// Time zone of the dates in the range, like `America/Sao_Paulo`, the local one by default. The events are
+    /// always in UTC, the zone is only a hint for the calendar.
+    #[arg(long, value_name = "ZONE")]
+    tz: Option<Tz>,
+    /// Write the calendar to this file instead of STDOUT.
+    #[arg(short, long, value_name = "FILE")]
+    output: Option<PathBuf>,
+}
+
+pub fn run_export(args: ExportArgs) -> Result<()> {
+    // The range is of days in the chosen time zone, if any, not necessarily the local one
+    let records: Vec<_> = history::load()?
+        .into_iter()
+        .filter(|record| {
+            let date = match args.tz {
+                Some(tz) => record.started_at.with_timezone(&tz).date_naive(),
+                None => record.date(),
+            };
+            args.range.contains(date)
+        })
+        .collect();
+
+    let contents = calendar(&records, args.tz, Utc::now());
+
+    match args.output {
+        Some(path) => fs::write(&path, contents).or_error(
+            TomateError::Io,
+            &render(t().write_report_failed, &[("path", &path.display())]),
+        ),
+        None => io::Write::write_all(&mut io::stdout(), contents.as_bytes()).or_error(
+            TomateError::Io,
+            &render(t().write_report_failed, &[("path", &"STDOUT")]),
+        ),
+    }
+}
+
+/// A `VCALENDAR` with an event per record, in UTC, so no `VTIMEZONE` is needed. `tz` is only a hint for the
+/// calendars that show it.
+fn calendar(records: &[Record], tz: Option<Tz>, now: DateTime<Utc>) -> String {
+    let mut lines = vec![
+        "BEGIN:VCALENDAR".to_owned(),
+        "VERSION:2.0".to_owned(),
+        "PRODID:-//tomate//tomate//EN".to_owned(),
+        "CALSCALE:GREGORIAN".to_owned(),
+    ];
+    if let Some(tz) = tz {
+        lines.push(format!("X-WR-TIMEZONE:{}", tz.name()));
+    }
+
+    for record in records {
+        lines.extend([
+            "BEGIN:VEVENT".to_owned(),
+            format!("UID:{}", uid(record)),
+            format!("DTSTAMP:{}", now.format("%Y%m%dT%H%M%SZ")),
+            format!("DTSTART:{}", utc(record.started_at)),
+            format!("DTEND:{}", utc(record.ended_at)),
+            format!("SUMMARY:{}", escape(&summary(record))),
+            format!("DESCRIPTION:{}", escape(&description(record))),
+            format!("CATEGORIES:{}", record.stage.name()),
+            "TRANSP:OPAQUE".to_owned(),
+            "END:VEVENT".to_owned(),
+        ]);
+    }
+
+    lines.push("END:VCALENDAR".to_owned());
+
+    lines.iter().map(|line| fold(line) + "\r\n").collect()
+}
+
+/// Stable across exports, so importing the same records twice updates the events instead of duplicating them.
+fn uid(record: &Record) -> String {
+    format!(
+        "{}-{}-{}@tomate",
+        utc(record.started_at),
+        record.stage.name(),
+        record.tomato,
+    )
+}
+
+/// A DATE-TIME in UTC, like `20240102T093000Z`.
+fn utc(date_time: DateTime<Local>) -> String {
+    date_time.with_timezone(&Utc).format("%Y%m%dT%H%M%SZ").to_string()
+}
+
+/// The task of the tomato, or the stage when there's none.
+fn summary(record: &Record) -> String {
+    match (record.stage, &record.task) {
+        (Stage::Work, Some(task)) => task.clone(),
+        (Stage::Work, None) => render(t().ics_work_summary, &[("tomato", &record.tomato)]),
+        (Stage::Rest, _) => t().ics_rest_summary.to_owned(),
+    }
+}
+
+/// How the timer went, the same details as `tomate history`.
+fn description(record: &Record) -> String {
+    let actual = Time::from(record.actual_secs as u32);
+    let planned = Time::from(record.planned_secs as u32);
+
+    let mut description = render(t().actual_of_planned, &[("actual", &actual), ("planned", &planned)]);
+
+    if record.pauses > 0 {
+        let paused_for = Time::from(record.paused_secs as u32);
+        description += &format!(
+            " · {}",
+            render(t().pauses, &[("pauses", &record.pauses), ("time", &paused_for)])
+        );
+    }
+    if record.overtime_secs > 0 {
+        description += &format!(" · +{}", Time::from(record.overtime_secs as u32));
+    }
+    if record.skipped {
+        description += &format!(" {}", t().skipped_tag);
+    }
+
+    description
+}
+
+/// Escapes a TEXT value, see RFC 5545, section 3.3.11.
+fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+
+    for char in text.chars() {
+        match char {
+            '\\' | ';' | ',' => {
+                escaped.push('\\');
+                escaped.push(char);
+            }
+            '\n' => escaped.push_str("\\n"),
+            '\r' => {}
+            _ => escaped.push(char),
+        }
+    }
+
+    escaped
+}
+
+/// Splits a content line every 75 bytes, continuing with a space, without breaking characters.
+fn fold(line: &str) -> String {
+    let mut folded = String::with_capacity(line.len());
+    let mut width = 0;
+
+    for char in line.chars() {
+        if width + char.len_utf8() > MAX_LINE_BYTES {
+            folded.push_str("\r\n ");
+            // The leading space counts towards the limit of the continuation line
+            width = 1;
+        }
+
+        folded.push(char);
+        width += char.len_utf8();
+    }
+
+    folded
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Like a calendar reads it, the folded lines joined and the values unescaped, by event.
+    fn parse(calendar: &str) -> Vec<Vec<(String, String)>> {
+        assert!(calendar.ends_with("\r\n"));
+        let mut lines: Vec<String> = Vec::new();
+
+        for line in calendar.trim_end_matches("\r\n").split("\r\n") {
+            assert!(line.len() <= MAX_LINE_BYTES, "{line:?}");
+
+            match line.strip_prefix(' ') {
+                Some(continuation) => lines.last_mut().unwrap().push_str(continuation),
+                None => lines.push(line.to_owned()),
+            }
+        }
+
+        let mut events = Vec::new();
+        for line in lines {
+            let (name, value) = line.split_once(':').unwrap();
+            match name {
+                "BEGIN" if value == "VEVENT" => events.push(Vec::new()),
+                _ => {
+                    if let Some(event) = events.last_mut() {
+                        event.push((name.to_owned(), unescape(value)));
+                    }
+                }
+            }
+        }
+
+        events
+    }
+
+    fn unescape(value: &str) -> String {
+        let mut unescaped = String::new();
+        let mut chars = value.chars();
+
+        while let Some(char) = chars.next() {
+            match char {
+                '\\' => match chars.next() {
+                    Some('n' | 'N') => unescaped.push('\n'),
+                    Some(escaped) => unescaped.push(escaped),
+                    None => panic!("dangling escape in {value:?}"),
+                },
+                _ => unescaped.push(char),
+            }
+        }
+
+        unescaped
+    }
+
+    fn property<'a>(event: &'a [(String, String)], name: &str) -> &'a str {
+        event
+            .iter()
+            .find(|(property, _)| property == name)
+            .map(|(_, value)| value.as_str())
+            .unwrap_or_else(|| panic!("no {name} in {event:?}"))
+    }
+
+    fn record(stage: &str, task: Option<&str>, started_at: &str, ended_at: &str) -> Record {
+        serde_json::from_value(serde_json::json!({
+            "stage": stage,
+            "tomato": 2,
+            "task": task,
+            "started_at": started_at,
+            "ended_at": ended_at,
+            "planned_secs": 1500,
+            "actual_secs": 1500,
+            "paused_secs": 0,
+            "pauses": 0,
+            "skipped": false,
+        }))
+        .unwrap()
+    }
+
+    fn now() -> DateTime<Utc> {
+        "2024-01-03T12:00:00Z".parse().unwrap()
+    }
+
+    #[test]
+    fn events_are_in_utc() {
+        let records = [
+            record(
+                "work",
+                Some("review"),
+                "2024-01-02T09:30:00-03:00",
+                "2024-01-02T09:55:00-03:00",
+            ),
+            record("rest", None, "2024-01-02T09:55:00-03:00", "2024-01-02T10:00:00-03:00"),
+        ];
+
+        for tz in [None, Some(Tz::Europe__Lisbon)] {
+            let events = parse(&calendar(&records, tz, now()));
+
+            assert_eq!(events.len(), 2);
+            assert_eq!(property(&events[0], "DTSTART"), "20240102T123000Z");
+            assert_eq!(property(&events[0], "DTEND"), "20240102T125500Z");
+            assert_eq!(property(&events[0], "SUMMARY"), "review");
+            assert_eq!(property(&events[1], "DTSTART"), "20240102T125500Z");
+            assert_eq!(property(&events[1], "DTEND"), "20240102T130000Z");
+            assert_eq!(property(&events[1], "SUMMARY"), t().ics_rest_summary);
+        }
+    }
+
+    #[test]
+    fn the_zone_is_only_a_hint() {
+        let records = [record("work", None, "2024-01-02T09:30:00Z", "2024-01-02T09:55:00Z")];
+
+        let calendar = calendar(&records, Some(Tz::America__Sao_Paulo), now());
+        assert!(calendar.contains("X-WR-TIMEZONE:America/Sao_Paulo\r\n"));
+        assert!(!calendar.contains("TZID"));
+
+        assert!(!super::calendar(&records, None, now()).contains("X-WR-TIMEZONE"));
+    }
+
+    #[test]
+    fn long_tasks_are_escaped_and_folded() {
+        let task = "revisar o PR 42, o do parser; depois o \\ do Windows\ne a tradução do café ☕ ".repeat(3);
+        let records = [record(
+            "work",
+            Some(&task),
+            "2024-01-02T09:30:00Z",
+            "2024-01-02T09:55:00Z",
+        )];
+
+        let calendar = calendar(&records, None, now());
+        let events = parse(&calendar);
+
+        assert!(calendar.contains("\r\n "));
+        assert_eq!(property(&events[0], "SUMMARY"), task);
+        assert_eq!(property(&events[0], "UID"), "20240102T093000Z-work-2@tomate");
+    }
+}
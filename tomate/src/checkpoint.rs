@@ -0,0 +1,102 @@
+use std::{fs, io, path::PathBuf, time::Duration};
+
+use chrono::{DateTime, Local};
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    error::{OrError, Result, TomateError},
+    i18n::{render, t},
+    plan::SessionPlan,
+    Stage,
+};
+
+/// How often the timer is saved while running, besides when it's paused or resumed and between stages.
+pub const INTERVAL: Duration = Duration::from_secs(10);
+
+/// What to do with the time between the last checkpoint and `tomate --resume`.
+#[derive(Clone, Copy, ValueEnum)]
+pub enum OfflineTime {
+    /// Ask when resuming, or discard it if there's no terminal to ask on.
+    Ask,
+    /// Count it as if the timer kept running, which may finish the interrupted stage.
+    Count,
+    /// Continue the timer from where it stopped.
+    Discard,
+}
+
+/// The sprint in progress, saved regularly so it can be continued after a crash or reboot.
+#[derive(Serialize, Deserialize)]
+pub struct Checkpoint {
+    pub plan: SessionPlan,
+    pub tomato: u32,
+    pub stage: Stage,
+    pub started_at: DateTime<Local>,
+    /// Duration of the stage, including the minutes added or removed while it ran.
+    pub stage_secs: u64,
+    pub remaining_secs: u64,
+    pub paused: bool,
+    /// Counted up so far after the tomato ended, while in overtime.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub overtime_secs: Option<u64>,
+    /// The stage hasn't started yet, the one before it being already in the history.
+    #[serde(default)]
+    pub upcoming: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub task: Option<String>,
+    pub saved_at: DateTime<Local>,
+}
+
+/// `$XDG_DATA_HOME/tomate/checkpoint.json`, next to the history.
+fn checkpoint_path() -> Result<PathBuf> {
+    Ok(dirs::data_dir()
+        .or_error(TomateError::Io, t().data_dir_missing)?
+        .join("tomate")
+        .join("checkpoint.json"))
+}
+
+impl Checkpoint {
+    pub fn load() -> Result<Self> {
+        let path = checkpoint_path()?;
+        let invalid = render(t().invalid_checkpoint, &[("path", &path.display())]);
+
+        let text = match fs::read_to_string(&path) {
+            Ok(text) => text,
+            Err(err) if err.kind() == io::ErrorKind::NotFound => {
+                return Err(TomateError::Io(t().no_checkpoint.to_owned()))
+            }
+            Err(err) => return Err(err).or_error(TomateError::Io, &invalid),
+        };
+
+        serde_json::from_str(&text).or_error(TomateError::Io, &invalid)
+    }
+
+    /// Writes to a temporary file first, so a crash while saving doesn't leave half a checkpoint.
+    pub fn save(&self) -> Result<()> {
+        let path = checkpoint_path()?;
+        let temporary = path.with_extension("json.tmp");
+
+        let write = || -> io::Result<()> {
+            if let Some(parent) = path.parent() {
+                fs::create_dir_all(parent)?;
+            }
+
+            fs::write(&temporary, serde_json::to_string(self)?)?;
+            fs::rename(&temporary, &path)
+        };
+
+        write().or_error(TomateError::Io, t().write_checkpoint_failed)
+    }
+
+    /// Forgets the checkpoint, once the sprint is over there's nothing to resume.
+    pub fn remove() {
+        if let Ok(path) = checkpoint_path() {
+            let _ = fs::remove_file(path);
+        }
+    }
+
+    /// How long tomate wasn't running since the checkpoint was saved.
+    pub fn offline(&self) -> Duration {
+        (Local::now() - self.saved_at).to_std().unwrap_or_default()
+    }
+}
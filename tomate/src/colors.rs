@@ -1,4 +1,43 @@
-pub use owo_colors::OwoColorize;
+use std::sync::OnceLock;
+
+use owo_colors::AnsiColors;
+use serde::Deserialize;
+
+use crate::i18n::{render, t};
+
+static PALETTE: OnceLock<Palette> = OnceLock::new();
+
+/// Colors used across the terminal output, configurable in the `[colors]` table of the config file.
+#[derive(Clone, Copy, Deserialize)]
+#[serde(default)]
+pub struct Palette {
+    pub work: Color,
+    pub rest: Color,
+    pub tomato: Color,
+    pub time: Color,
+    pub task: Color,
+}
+
+impl Default for Palette {
+    fn default() -> Self {
+        Self {
+            work: Color(AnsiColors::Magenta),
+            rest: Color(AnsiColors::Cyan),
+            tomato: Color(AnsiColors::Red),
+            time: Color(AnsiColors::Blue),
+            task: Color(AnsiColors::Yellow),
+        }
+    }
+}
+
+/// Sets the palette used by [`palette`], can only be called once, before any output.
+pub fn set_palette(palette: Palette) {
+    let _ = PALETTE.set(palette);
+}
+
+pub fn palette() -> &'static Palette {
+    PALETTE.get_or_init(Palette::default)
+}
 
 #[macro_export]
 macro_rules! show {
@@ -25,3 +64,36 @@ macro_rules! showln {
         ::std::println!();
     };
 }
+
+/// A color name from the config file, like "magenta" or "bright_red".
+#[derive(Clone, Copy, Deserialize)]
+#[serde(try_from = "String")]
+pub struct Color(pub AnsiColors);
+
+impl TryFrom<String> for Color {
+    type Error = String;
+
+    fn try_from(name: String) -> Result<Self, Self::Error> {
+        let color = match name.as_str() {
+            "black" => AnsiColors::Black,
+            "red" => AnsiColors::Red,
+            "green" => AnsiColors::Green,
+            "yellow" => AnsiColors::Yellow,
+            "blue" => AnsiColors::Blue,
+            "magenta" => AnsiColors::Magenta,
+            "cyan" => AnsiColors::Cyan,
+            "white" => AnsiColors::White,
+            "bright_black" => AnsiColors::BrightBlack,
+            "bright_red" => AnsiColors::BrightRed,
+            "bright_green" => AnsiColors::BrightGreen,
+            "bright_yellow" => AnsiColors::BrightYellow,
+            "bright_blue" => AnsiColors::BrightBlue,
+            "bright_magenta" => AnsiColors::BrightMagenta,
+            "bright_cyan" => AnsiColors::BrightCyan,
+            "bright_white" => AnsiColors::BrightWhite,
+            _ => return Err(render(t().unknown_color, &[("name", &name)])),
+        };
+
+        Ok(Self(color))
+    }
+}
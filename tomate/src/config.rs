@@ -0,0 +1,234 @@
+use std::{
+    fs, io,
+    path::{Path, PathBuf},
+};
+
+use clap::Subcommand;
+use owo_colors::OwoColorize;
+use serde::Deserialize;
+
+use crate::{
+    activities::ActivityConfig,
+    adaptive_rest::AdaptiveRestConfig,
+    colors::Palette,
+    error::{warn, OrError, Result, TomateError},
+    goals::GoalConfig,
+    hooks::HookConfig,
+    i18n::{render, t, Lang},
+    idle::IdleConfig,
+    notification::{Chain, NotificationConfig},
+    plan::{PlanFile, SessionPlan},
+    showln,
+    sound::SoundConfig,
+};
+
+/// Settings read from `$XDG_CONFIG_HOME/tomate/config.toml`, CLI arguments take precedence over them.
+#[derive(Default, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    pub lang: Option<Lang>,
+    pub session: PlanFile,
+    pub reward_emojis: Option<Vec<String>>,
+    pub micro_management_emojis: Option<Vec<String>>,
+    pub messages: Messages,
+    pub colors: Palette,
+    pub notifications: NotificationConfig,
+    pub sounds: SoundConfig,
+    pub hooks: HookConfig,
+    pub idle: IdleConfig,
+    pub overtime: OvertimeConfig,
+    pub goals: GoalConfig,
+    pub adaptive_rest: AdaptiveRestConfig,
+    pub break_activities: ActivityConfig,
+    #[serde(skip)]
+    unknown_keys: Vec<String>,
+}
+
+/// Notification templates overriding the ones from the language catalog, see [`render`] for the placeholders.
+#[derive(Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct Messages {
+    work_start: Option<String>,
+    halfway: Option<String>,
+    overtime: Option<String>,
+    work_done: Option<String>,
+    long_work_done: Option<String>,
+}
+
+impl Messages {
+    pub fn work_start(&self) -> &str {
+        self.work_start.as_deref().unwrap_or(t().work_start_notification)
+    }
+
+    pub fn halfway(&self) -> &str {
+        self.halfway.as_deref().unwrap_or(t().halfway_notification)
+    }
+
+    pub fn overtime(&self) -> &str {
+        self.overtime.as_deref().unwrap_or(t().overtime_notification)
+    }
+
+    pub fn work_done(&self) -> &str {
+        self.work_done.as_deref().unwrap_or(t().work_done_notification)
+    }
+
+    pub fn long_work_done(&self) -> &str {
+        self.long_work_done
+            .as_deref()
+            .unwrap_or(t().long_work_done_notification)
+    }
+}
+
+/// The `[overtime]` table of the config file, also enabled by `--overtime`.
+#[derive(Clone, Copy, Default, Deserialize)]
+#[serde(default)]
+pub struct OvertimeConfig {
+    /// Keep counting up when a tomato ends, until the break is taken.
+    pub enabled: bool,
+    /// How much of the overtime is added to the next break, like 0.2 for a minute every 5.
+    pub rest_ratio: f64,
+}
+
+fn config_path() -> Result<PathBuf> {
+    Ok(dirs::config_dir()
+        .or_error(TomateError::Config, t().config_dir_missing)?
+        .join("tomate")
+        .join("config.toml"))
+}
+
+impl Config {
+    /// Loads the config file, a missing one means all defaults.
+    pub fn load() -> Result<Self> {
+        let Some((config, unknown_keys)) = parse(&config_path()?)? else {
+            return Ok(Self::default());
+        };
+
+        Self { unknown_keys, ..config }.validate()
+    }
+
+    /// Warns about the unknown keys found while loading, called once the language is set.
+    pub fn warn_unknown_keys(&self) {
+        let Ok(path) = config_path() else {
+            return;
+        };
+
+        for key in &self.unknown_keys {
+            warn(&TomateError::Config(render(
+                t().unknown_config_key,
+                &[("key", key), ("path", &path.display())],
+            )));
+        }
+    }
+
+    fn validate(self) -> Result<Self> {
+        let is_empty = |emojis: &Option<Vec<String>>| emojis.as_ref().is_some_and(Vec::is_empty);
+
+        (!is_empty(&self.reward_emojis)).or_error(
+            TomateError::Config,
+            &render(t().empty_emoji_list, &[("list", &"reward_emojis")]),
+        )?;
+        (!is_empty(&self.micro_management_emojis)).or_error(
+            TomateError::Config,
+            &render(t().empty_emoji_list, &[("list", &"micro_management_emojis")]),
+        )?;
+        self.notifications
+            .build()
+            .is_some()
+            .or_error(TomateError::Config, t().notification_command_missing)?;
+        (0.0..=1.0)
+            .contains(&self.sounds.volume)
+            .or_error(TomateError::Config, t().invalid_volume)?;
+        self.idle
+            .is_valid()
+            .or_error(TomateError::Config, t().idle_command_missing)?;
+        (0.0..=1.0)
+            .contains(&self.overtime.rest_ratio)
+            .or_error(TomateError::Config, t().invalid_rest_ratio)?;
+        self.goals.is_valid().or_error(TomateError::Config, t().invalid_goal)?;
+        self.adaptive_rest
+            .is_valid()
+            .or_error(TomateError::Config, t().invalid_adaptive_rest)?;
+        self.break_activities
+            .is_valid()
+            .or_error(TomateError::Config, t().invalid_activity_weight)?;
+
+        Ok(self)
+    }
+
+    /// The backends listed in the `[notifications]` table.
+    pub fn notifier(&self) -> Result<Chain> {
+        self.notifications
+            .build()
+            .or_error(TomateError::Config, t().notification_command_missing)
+    }
+
+    /// The plan described by the `[session]` table, on top of the defaults.
+    pub fn take_plan(&mut self) -> Result<SessionPlan> {
+        std::mem::take(&mut self.session).apply(SessionPlan::default())
+    }
+}
+
+/// Returns the config and the keys that weren't recognized, or `None` if the file doesn't exist.
+fn parse(path: &Path) -> Result<Option<(Config, Vec<String>)>> {
+    let contents = match fs::read_to_string(path) {
+        Ok(contents) => contents,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(None),
+        Err(err) => {
+            return Err(err).or_error(
+                TomateError::Config,
+                &render(t().read_config_failed, &[("path", &path.display())]),
+            )
+        }
+    };
+
+    let mut unknown_keys = Vec::new();
+
+    let config = serde_ignored::deserialize(toml::Deserializer::new(&contents), |key| {
+        unknown_keys.push(key.to_string());
+    })
+    .map_err(|err| {
+        TomateError::Config(render(
+            t().invalid_config,
+            &[("path", &path.display()), ("error", &err)],
+        ))
+    })?;
+
+    Ok(Some((config, unknown_keys)))
+}
+
+#[derive(Subcommand)]
+pub enum ConfigCommand {
+    /// Report invalid keys and values in the config file.
+    Check,
+}
+
+pub fn run_config_command(command: ConfigCommand) -> Result<()> {
+    match command {
+        ConfigCommand::Check => check(),
+    }
+}
+
+fn check() -> Result<()> {
+    let path = config_path()?;
+
+    let Some((mut config, unknown_keys)) = parse(&path)? else {
+        showln!(render(t().no_config_file, &[("path", &path.display())]).dimmed());
+        return Ok(());
+    };
+
+    // The last one is left to `main`, so it exits like any other error
+    if let Some((last, others)) = unknown_keys.split_last() {
+        for key in others {
+            showln!(t().error.red(), ": ", render(t().unknown_key, &[("key", key)]));
+        }
+
+        return Err(TomateError::Config(render(t().unknown_key, &[("key", last)])));
+    }
+
+    config.take_plan()?.validate()?;
+    config.validate()?;
+
+    showln!(render(t().config_valid, &[("path", &path.display())]).green());
+
+    Ok(())
+}
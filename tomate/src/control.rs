@@ -0,0 +1,483 @@
+use std::{
+    env, fs,
+    io::{self, BufRead, BufReader},
+    mem,
+    os::unix::{
+        fs::{DirBuilderExt, MetadataExt},
+        net::{UnixListener, UnixStream},
+    },
+    path::{Path, PathBuf},
+    sync::{
+        mpsc::{self, Receiver, SyncSender},
+        Arc, Mutex,
+    },
+};
+
+use clap::{Args, ValueEnum};
+use owo_colors::OwoColorize;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    colors::palette,
+    error::{OrError, Result, TomateError},
+    i18n::{render, t},
+    json_lines, showln,
+    time::Time,
+    Stage,
+};
+
+/// What drives the timer, a key pressed on STDIN or a command from the control socket.
+pub enum Input {
+    Key(u8),
+    Control(Control),
+    /// The terminal was resized, nothing to do but redrawing.
+    Resize,
+}
+
+/// A channel of inputs that can't be closed, like the one of STDIN, see [`crate::stdin::spawn_stdin_channel`].
+pub fn input_channel() -> (SyncSender<Input>, Receiver<Input>) {
+    let (sender, receiver) = mpsc::sync_channel(0);
+    mem::forget(sender.clone());
+
+    (sender, receiver)
+}
+
+impl Input {
+    /// The control bound to the key, see [`Control::from_key`].
+    pub fn control(self) -> Option<Control> {
+        match self {
+            Self::Key(key) => Control::from_key(key),
+            Self::Control(control) => Some(control),
+            Self::Resize => None,
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+pub enum Control {
+    Pause,
+    Resume,
+    TogglePause,
+    Skip,
+    AddMinute,
+    RemoveMinute,
+    Restart,
+    Stop,
+}
+
+impl Control {
+    /// The keys listed in the help footer, anything else is ignored.
+    pub fn from_key(key: u8) -> Option<Self> {
+        match key {
+            b' ' => Some(Self::TogglePause),
+            b's' => Some(Self::Skip),
+            b'+' | b'=' => Some(Self::AddMinute),
+            b'-' => Some(Self::RemoveMinute),
+            b'r' => Some(Self::Restart),
+            b'q' => Some(Self::Stop),
+            _ => None,
+        }
+    }
+}
+
+/// Snapshot of the timer, updated at every tick and answered to `tomate ctl status`.
+#[derive(Clone, Default, Serialize, Deserialize)]
+pub struct Status {
+    pub stage: Option<Stage>,
+    pub remaining_secs: u64,
+    pub paused: bool,
+    /// Counted up after the tomato ended, in overtime mode.
+    #[serde(default)]
+    pub overtime_secs: u64,
+    pub cycle: u32,
+    pub cycles: u32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub task: Option<String>,
+    /// Progress towards the daily and weekly goals, like "5/8 today".
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub goal: Option<String>,
+}
+
+pub type SharedStatus = Arc<Mutex<Status>>;
+
+/// A request line sent to the socket, like `{"command":"pause"}`.
+#[derive(Clone, Copy, Serialize, Deserialize, ValueEnum)]
+#[serde(tag = "command", rename_all = "lowercase")]
+pub enum Request {
+    /// Pause the current timer.
+    Pause,
+    /// Resume a paused timer.
+    Resume,
+    /// Skip the current timer.
+    Skip,
+    /// Show the current timer.
+    Status,
+    /// End the session and shut the daemon down.
+    Stop,
+}
+
+impl Request {
+    /// What to forward to the timer, `None` for requests answered by the socket thread.
+    pub fn control(self) -> Option<Control> {
+        match self {
+            Self::Pause => Some(Control::Pause),
+            Self::Resume => Some(Control::Resume),
+            Self::Skip => Some(Control::Skip),
+            Self::Stop => Some(Control::Stop),
+            Self::Status => None,
+        }
+    }
+}
+
+/// The line answered to every request, `status` is only present for the `status` request.
+#[derive(Serialize, Deserialize)]
+pub struct Response {
+    pub ok: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub status: Option<Status>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl Response {
+    fn ok(status: Option<Status>) -> Self {
+        Self {
+            ok: true,
+            status,
+            error: None,
+        }
+    }
+}
+
+#[derive(Args)]
+pub struct SocketArgs {
+    /// Path of the control socket, defaults to `$XDG_RUNTIME_DIR/tomate.sock`, or to `tomate-<uid>/tomate.sock` in
+    /// the temporary directory without a runtime directory.
+    #[arg(long, value_name = "PATH")]
+    socket: Option<PathBuf>,
+}
+
+impl SocketArgs {
+    pub fn path(&self) -> Result<PathBuf> {
+        if let Some(path) = &self.socket {
+            return Ok(path.clone());
+        }
+
+        let dir = match dirs::runtime_dir() {
+            Some(dir) => dir,
+            None => private_temp_dir()?,
+        };
+
+        Ok(dir.join("tomate.sock"))
+    }
+}
+
+/// A directory only this user can enter, in the temporary directory shared with everyone else, where anyone could
+/// put a socket of their own to receive the controls, or send them.
+fn private_temp_dir() -> Result<PathBuf> {
+    let dir = env::temp_dir().join(format!("tomate-{}", unsafe { libc::getuid() }));
+    create_private_dir(&dir)?;
+
+    Ok(dir)
+}
+
+/// Creates `dir` with access for this user only, or checks that it's already like that.
+fn create_private_dir(dir: &Path) -> Result<()> {
+    // Fails if it already exists, then it's checked instead
+    let _ = fs::DirBuilder::new().mode(0o700).create(dir);
+
+    // Not followed, a link could point anywhere
+    let is_private = fs::symlink_metadata(dir).is_ok_and(|metadata| {
+        metadata.is_dir() && metadata.uid() == unsafe { libc::getuid() } && metadata.mode() & 0o077 == 0
+    });
+
+    is_private.or_error(
+        TomateError::Io,
+        &render(t().socket_dir_unsafe, &[("path", &dir.display())]),
+    )
+}
+
+#[derive(Args)]
+pub struct CtlArgs {
+    #[arg(value_enum)]
+    request: Request,
+    /// Print the JSON response instead of a human readable line.
+    #[arg(long)]
+    json: bool,
+    #[command(flatten)]
+    socket: SocketArgs,
+}
+
+/// Binds the control socket and forwards the requests to the timer through the returned receiver.
+pub fn spawn_socket_channel(path: &Path, status: SharedStatus) -> Result<Receiver<Input>> {
+    let listener = bind(path)?;
+    let (sender, receiver) = input_channel();
+
+    json_lines::serve(listener, move |_, stream| handle_client(stream, &sender, &status));
+
+    Ok(receiver)
+}
+
+/// Binds to `path`, replacing a leftover socket file if no daemon answers on it.
+fn bind(path: &Path) -> Result<UnixListener> {
+    let bind_failed = render(t().socket_bind_failed, &[("path", &path.display())]);
+
+    match UnixListener::bind(path) {
+        Ok(listener) => Ok(listener),
+        Err(err) if err.kind() == io::ErrorKind::AddrInUse => {
+            if UnixStream::connect(path).is_ok() {
+                return Err(TomateError::Io(render(
+                    t().daemon_already_running,
+                    &[("path", &path.display())],
+                )));
+            }
+
+            fs::remove_file(path).or_error(TomateError::Io, &bind_failed)?;
+            UnixListener::bind(path).or_error(TomateError::Io, &bind_failed)
+        }
+        Err(err) => Err(err).or_error(TomateError::Io, &bind_failed),
+    }
+}
+
+/// Answers every request of a `tomate ctl`, or of anything else talking to the socket.
+fn handle_client(stream: UnixStream, sender: &SyncSender<Input>, status: &SharedStatus) -> io::Result<()> {
+    json_lines::read_lines(stream.try_clone()?, |request: serde_json::Result<Request>| {
+        let response = match request {
+            Ok(request) => match request.control() {
+                Some(control) => {
+                    // Only fails when the session is over and the daemon is exiting
+                    let _ = sender.send(Input::Control(control));
+                    Response::ok(None)
+                }
+                None => Response::ok(Some(status.lock().unwrap().clone())),
+            },
+            Err(err) => Response {
+                ok: false,
+                status: None,
+                error: Some(err.to_string()),
+            },
+        };
+
+        json_lines::write_line(&stream, &response)
+    })
+}
+
+/// Sends a single request to a running daemon and prints its response.
+pub fn run_ctl(args: CtlArgs) -> Result<()> {
+    let path = args.socket.path()?;
+    let connect_failed = render(t().daemon_connect_failed, &[("path", &path.display())]);
+
+    let stream = UnixStream::connect(&path).or_error(TomateError::Io, &connect_failed)?;
+    json_lines::write_line(&stream, &args.request).or_error(TomateError::Io, &connect_failed)?;
+
+    let mut line = String::new();
+    BufReader::new(stream)
+        .read_line(&mut line)
+        .or_error(TomateError::Io, &connect_failed)?;
+
+    if args.json {
+        print!("{line}");
+        return Ok(());
+    }
+
+    let response = serde_json::from_str::<Response>(&line).or_error(TomateError::Io, &connect_failed)?;
+
+    match (response.ok, response.status) {
+        (true, Some(status)) => show_status(&status),
+        (true, None) => {}
+        (false, _) => return Err(TomateError::Control(response.error.unwrap_or_default())),
+    }
+
+    Ok(())
+}
+
+fn show_status(status: &Status) {
+    let Some(stage) = status.stage else {
+        showln!(t().daemon_idle.dimmed());
+        return;
+    };
+
+    let time = if status.overtime_secs > 0 {
+        format!("+{}", Time::from(status.overtime_secs as u32))
+    } else {
+        Time::from(status.remaining_secs as u32).to_string()
+    };
+    let progress = render(t().progress, &[("cycle", &status.cycle), ("cycles", &status.cycles)]);
+    let task = status.task.as_ref().map(|task| format!(": {task}")).unwrap_or_default();
+    let goal = status
+        .goal
+        .as_ref()
+        .map(|goal| format!(" · {goal}"))
+        .unwrap_or_default();
+
+    showln!(
+        stage,
+        " ",
+        time.color(palette().time.0),
+        "  ",
+        progress.dimmed(),
+        goal.dimmed(),
+        task.color(palette().task.0),
+        if status.paused {
+            format!(" {}", t().paused.red())
+        } else {
+            String::new()
+        },
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{io::Write, os::unix::fs::PermissionsExt, time::Duration};
+
+    use super::*;
+
+    /// A daemon socket in a directory of its own, with a client connected to it.
+    struct Fixture {
+        dir: tempfile::TempDir,
+        status: SharedStatus,
+        inputs: Receiver<Input>,
+        writer: UnixStream,
+        reader: BufReader<UnixStream>,
+    }
+
+    impl Fixture {
+        fn new() -> Self {
+            let dir = tempfile::tempdir().unwrap();
+            let path = dir.path().join("tomate.sock");
+            let status = SharedStatus::default();
+            let inputs = spawn_socket_channel(&path, status.clone()).unwrap();
+
+            let writer = UnixStream::connect(&path).unwrap();
+            writer.set_read_timeout(Some(Duration::from_secs(5))).unwrap();
+            let reader = BufReader::new(writer.try_clone().unwrap());
+
+            Self {
+                dir,
+                status,
+                inputs,
+                writer,
+                reader,
+            }
+        }
+
+        fn send(&mut self, line: &str) {
+            writeln!(self.writer, "{line}").unwrap();
+        }
+
+        fn response(&mut self) -> Response {
+            let mut line = String::new();
+            self.reader.read_line(&mut line).unwrap();
+            serde_json::from_str(&line).unwrap()
+        }
+
+        fn forwarded(&self) -> Option<Control> {
+            match self.inputs.recv_timeout(Duration::from_secs(5)) {
+                Ok(Input::Control(control)) => Some(control),
+                _ => None,
+            }
+        }
+    }
+
+    #[test]
+    fn status_is_answered_from_the_latest_update() {
+        let mut fixture = Fixture::new();
+
+        fixture.send(r#"{"command":"status"}"#);
+        let response = fixture.response();
+        assert!(response.ok);
+        assert!(response.status.unwrap().stage.is_none());
+
+        *fixture.status.lock().unwrap() = Status {
+            stage: Some(Stage::Work),
+            remaining_secs: 90,
+            paused: true,
+            cycle: 2,
+            cycles: 4,
+            task: Some("review".to_owned()),
+            ..Status::default()
+        };
+
+        fixture.send(r#"{"command":"status"}"#);
+        let status = fixture.response().status.unwrap();
+        assert!(matches!(status.stage, Some(Stage::Work)));
+        assert_eq!(status.remaining_secs, 90);
+        assert!(status.paused);
+        assert_eq!((status.cycle, status.cycles), (2, 4));
+        assert_eq!(status.task.as_deref(), Some("review"));
+    }
+
+    #[test]
+    fn controls_are_forwarded_to_the_timer() {
+        let mut fixture = Fixture::new();
+
+        fixture.send(r#"{"command":"pause"}"#);
+        assert!(matches!(fixture.forwarded(), Some(Control::Pause)));
+        let response = fixture.response();
+        assert!(response.ok);
+        assert!(response.status.is_none());
+
+        fixture.send(r#"{"command":"skip"}"#);
+        assert!(matches!(fixture.forwarded(), Some(Control::Skip)));
+        assert!(fixture.response().ok);
+    }
+
+    #[test]
+    fn malformed_lines_are_answered_with_an_error() {
+        let mut fixture = Fixture::new();
+
+        fixture.send("not json");
+        let response = fixture.response();
+        assert!(!response.ok);
+        assert!(response.error.is_some_and(|error| !error.is_empty()));
+
+        // The connection is still usable after it
+        fixture.send(r#"{"command":"status"}"#);
+        assert!(fixture.response().ok);
+    }
+
+    #[test]
+    fn a_silent_client_doesnt_block_the_others() {
+        // The client of the fixture connects first and never writes
+        let fixture = Fixture::new();
+
+        let mut other = UnixStream::connect(fixture.dir.path().join("tomate.sock")).unwrap();
+        other.set_read_timeout(Some(Duration::from_secs(5))).unwrap();
+        writeln!(other, r#"{{"command":"status"}}"#).unwrap();
+
+        let mut line = String::new();
+        BufReader::new(other).read_line(&mut line).unwrap();
+        assert!(serde_json::from_str::<Response>(&line).unwrap().ok);
+    }
+
+    #[test]
+    fn the_socket_directory_must_be_private() {
+        let temp = tempfile::tempdir().unwrap();
+
+        let dir = temp.path().join("tomate-new");
+        assert!(create_private_dir(&dir).is_ok());
+        assert_eq!(fs::metadata(&dir).unwrap().mode() & 0o777, 0o700);
+        // Again, as when the daemon starts a second time
+        assert!(create_private_dir(&dir).is_ok());
+
+        let shared = temp.path().join("tomate-shared");
+        fs::DirBuilder::new().mode(0o777).create(&shared).unwrap();
+        fs::set_permissions(&shared, fs::Permissions::from_mode(0o777)).unwrap();
+        assert!(matches!(create_private_dir(&shared), Err(TomateError::Io(_))));
+
+        let link = temp.path().join("tomate-link");
+        std::os::unix::fs::symlink(&dir, &link).unwrap();
+        assert!(matches!(create_private_dir(&link), Err(TomateError::Io(_))));
+    }
+
+    #[test]
+    fn a_second_daemon_is_refused() {
+        let fixture = Fixture::new();
+        let path = fixture.dir.path().join("tomate.sock");
+
+        assert!(matches!(
+            spawn_socket_channel(&path, SharedStatus::default()),
+            Err(TomateError::Io(_))
+        ));
+    }
+}
@@ -1,36 +1,97 @@
+use std::{fmt, process::ExitCode};
+
 use owo_colors::OwoColorize;
 
-pub trait UnwrapOrExplode<T> {
-    fn unwrap_or_explode(self, message: &str) -> T;
+use crate::{i18n::t, showln};
+
+pub type Result<T, E = TomateError> = std::result::Result<T, E>;
+
+/// Everything that can go wrong, with the message shown to the user. Only `main` ends the
+/// process, with the exit code of the kind of error, see the README.
+#[derive(Debug)]
+pub enum TomateError {
+    /// Invalid config file, plan file or flag value.
+    Config(String),
+    /// Reading or writing files, sockets and connections, also notifications and the status bar, which are only
+    /// warned about.
+    Io(String),
+    /// Reading from or writing to the terminal.
+    Terminal(String),
+    /// A request refused by the daemon, like pausing when there's no timer.
+    Control(String),
 }
 
-impl UnwrapOrExplode<()> for bool {
-    fn unwrap_or_explode(self, message: &str) {
-        if let false = self {
-            explode_error(message);
-        }
+impl TomateError {
+    pub fn exit_code(&self) -> ExitCode {
+        // Starting at 3, clap already exits with 2 on invalid arguments
+        let code = match self {
+            Self::Config(_) => 3,
+            Self::Io(_) => 4,
+            Self::Terminal(_) => 5,
+            Self::Control(_) => 6,
+        };
+
+        ExitCode::from(code)
     }
 }
 
-impl<T> UnwrapOrExplode<T> for Option<T> {
-    fn unwrap_or_explode(self, message: &str) -> T {
-        match self {
-            Some(inner) => inner,
-            None => explode_error(message),
-        }
+impl fmt::Display for TomateError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let (Self::Config(message) | Self::Io(message) | Self::Terminal(message) | Self::Control(message)) = self;
+
+        f.write_str(message)
     }
 }
 
-impl<T, E> UnwrapOrExplode<T> for Result<T, E> {
-    fn unwrap_or_explode(self, message: &str) -> T {
-        match self {
-            Ok(inner) => inner,
-            Err(_) => explode_error(message),
+impl std::error::Error for TomateError {}
+
+/// Turns a failure into a [`TomateError`], with a message for the user. The cause, if any, is kept after it, like
+/// "Permission denied", since the message alone rarely says what to fix.
+pub trait OrError<T> {
+    fn or_error(self, kind: fn(String) -> TomateError, message: &str) -> Result<T>;
+}
+
+impl OrError<()> for bool {
+    fn or_error(self, kind: fn(String) -> TomateError, message: &str) -> Result<()> {
+        if self {
+            Ok(())
+        } else {
+            Err(kind(message.to_owned()))
         }
     }
 }
 
-fn explode_error(message: &str) -> ! {
-    crate::showln!("Error".red(), ": ", format_args!("{message}"));
-    std::process::exit(1)
+impl<T> OrError<T> for Option<T> {
+    fn or_error(self, kind: fn(String) -> TomateError, message: &str) -> Result<T> {
+        self.ok_or_else(|| kind(message.to_owned()))
+    }
+}
+
+impl<T, E: fmt::Display> OrError<T> for Result<T, E> {
+    fn or_error(self, kind: fn(String) -> TomateError, message: &str) -> Result<T> {
+        self.map_err(|error| kind(format!("{message}: {error}")))
+    }
+}
+
+/// For failures that don't have to stop anything, shown as warnings.
+pub trait OrWarn<T> {
+    fn or_warn(self) -> Option<T>;
+}
+
+impl<T> OrWarn<T> for Result<T> {
+    fn or_warn(self) -> Option<T> {
+        self.map_err(|error| warn(&error)).ok()
+    }
+}
+
+pub fn warn(error: &TomateError) {
+    showln!(t().warning.yellow(), ": ", error);
+}
+
+/// Shows the error that stopped `main`, with the terminal back to normal.
+pub fn report(error: &TomateError) -> ExitCode {
+    crate::terminal::restore();
+    showln!(t().error.red(), ": ", error);
+
+    error.exit_code()
 }
@@ -0,0 +1,298 @@
+use std::collections::BTreeMap;
+
+use chrono::{Datelike, Days, Local, NaiveDate};
+use serde::Deserialize;
+
+use crate::{
+    error::Result,
+    history,
+    i18n::{render, t},
+    Stage,
+};
+
+/// The `[goals]` table of the config file, the flags take precedence over it.
+#[derive(Clone, Copy, Default, Deserialize)]
+#[serde(default)]
+pub struct GoalConfig {
+    /// Tomatoes a day.
+    pub daily: Option<u32>,
+    /// Tomatoes a week, from Monday to Sunday.
+    pub weekly: Option<u32>,
+}
+
+impl GoalConfig {
+    pub fn is_valid(&self) -> bool {
+        self.daily != Some(0) && self.weekly != Some(0)
+    }
+}
+
+#[derive(Clone, Copy)]
+pub enum Period {
+    Day,
+    Week,
+}
+
+/// Progress towards the goals, counted from the history so earlier sessions count too.
+pub struct Goals {
+    config: GoalConfig,
+    date: NaiveDate,
+    today: u32,
+    this_week: u32,
+    /// Consecutive days up to today with the daily goal reached, today only once it is.
+    daily_streak: u32,
+    /// Same as `daily_streak`, in weeks.
+    weekly_streak: u32,
+}
+
+impl Goals {
+    /// `None` without any goal set.
+    pub fn load(config: GoalConfig) -> Result<Option<Self>> {
+        if config.daily.is_none() && config.weekly.is_none() {
+            return Ok(None);
+        }
+
+        let mut goals = Self {
+            config,
+            date: Local::now().date_naive(),
+            today: 0,
+            this_week: 0,
+            daily_streak: 0,
+            weekly_streak: 0,
+        };
+        goals.refresh()?;
+
+        Ok(Some(goals))
+    }
+
+    /// Counts again after a tomato was recorded, returning the goals it just reached.
+    pub fn refresh(&mut self) -> Result<Vec<Period>> {
+        let date = Local::now().date_naive();
+
+        let mut days = BTreeMap::<NaiveDate, u32>::new();
+        for record in history::load()? {
+            if matches!(record.stage, Stage::Work) && !record.skipped {
+                *days.entry(record.date()).or_default() += 1;
+            }
+        }
+
+        Ok(self.count(&days, date))
+    }
+
+    /// Updates the counts from the completed tomatoes of each day, as of `date`, returning the goals reached since
+    /// they were last updated.
+    fn count(&mut self, days: &BTreeMap<NaiveDate, u32>, date: NaiveDate) -> Vec<Period> {
+        let on_day = |date: NaiveDate| days.get(&date).copied().unwrap_or_default();
+        let on_week = |date: NaiveDate| {
+            days.iter()
+                .filter(|(day, _)| day.iso_week() == date.iso_week())
+                .map(|(_, count)| count)
+                .sum()
+        };
+
+        // A new day or week starts from zero, whatever was counted before it
+        let today_before = if date == self.date { self.today } else { 0 };
+        let week_before = if date.iso_week() == self.date.iso_week() {
+            self.this_week
+        } else {
+            0
+        };
+
+        self.date = date;
+        self.today = on_day(date);
+        self.this_week = on_week(date);
+        self.daily_streak = self
+            .config
+            .daily
+            .map_or(0, |goal| streak(on_day, goal, date, Days::new(1)));
+        self.weekly_streak = self
+            .config
+            .weekly
+            .map_or(0, |goal| streak(on_week, goal, date, Days::new(7)));
+
+        let reached = |goal: Option<u32>, before: u32, now: u32| goal.is_some_and(|goal| before < goal && now >= goal);
+
+        let mut periods = Vec::new();
+        if reached(self.config.daily, today_before, self.today) {
+            periods.push(Period::Day);
+        }
+        if reached(self.config.weekly, week_before, self.this_week) {
+            periods.push(Period::Week);
+        }
+
+        periods
+    }
+
+    /// Like "5/8 today · 12/30 this week", for the banner, the reward and the bars.
+    pub fn progress(&self) -> String {
+        let mut parts = Vec::new();
+
+        if let Some(goal) = self.config.daily {
+            parts.push(render(t().goal_today, &[("count", &self.today), ("goal", &goal)]));
+        }
+        if let Some(goal) = self.config.weekly {
+            parts.push(render(t().goal_week, &[("count", &self.this_week), ("goal", &goal)]));
+        }
+
+        parts.join(" · ")
+    }
+
+    /// The message celebrating a goal just reached, with the streak it extends.
+    pub fn reached_message(&self, period: Period) -> String {
+        match period {
+            Period::Day => render(
+                t().daily_goal_reached,
+                &[
+                    ("goal", &self.config.daily.unwrap_or_default()),
+                    ("streak", &self.daily_streak),
+                ],
+            ),
+            Period::Week => render(
+                t().weekly_goal_reached,
+                &[
+                    ("goal", &self.config.weekly.unwrap_or_default()),
+                    ("streak", &self.weekly_streak),
+                ],
+            ),
+        }
+    }
+}
+
+/// Consecutive periods with at least `goal` tomatoes, going back from the one of `date`, which
+/// only breaks the streak once it's over.
+fn streak(count: impl Fn(NaiveDate) -> u32, goal: u32, date: NaiveDate, period: Days) -> u32 {
+    let mut date = date;
+    if count(date) < goal {
+        date = date - period;
+    }
+
+    let mut streak = 0;
+    while count(date) >= goal {
+        streak += 1;
+        date = date - period;
+    }
+
+    streak
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn date(text: &str) -> NaiveDate {
+        text.parse().unwrap()
+    }
+
+    /// Completed tomatoes by day.
+    fn completed(counts: &[(&str, u32)]) -> BTreeMap<NaiveDate, u32> {
+        counts.iter().map(|(day, count)| (date(day), *count)).collect()
+    }
+
+    fn daily_streak(days: &BTreeMap<NaiveDate, u32>, goal: u32, today: &str) -> u32 {
+        streak(
+            |date| days.get(&date).copied().unwrap_or_default(),
+            goal,
+            date(today),
+            Days::new(1),
+        )
+    }
+
+    fn goals(daily: Option<u32>, weekly: Option<u32>, today: &str) -> Goals {
+        Goals {
+            config: GoalConfig { daily, weekly },
+            date: date(today),
+            today: 0,
+            this_week: 0,
+            daily_streak: 0,
+            weekly_streak: 0,
+        }
+    }
+
+    fn kinds(periods: Vec<Period>) -> Vec<&'static str> {
+        periods
+            .into_iter()
+            .map(|period| match period {
+                Period::Day => "day",
+                Period::Week => "week",
+            })
+            .collect()
+    }
+
+    #[test]
+    fn a_gap_day_breaks_the_streak() {
+        let days = completed(&[
+            ("2026-10-12", 4),
+            ("2026-10-13", 4),
+            ("2026-10-15", 4),
+            ("2026-10-16", 5),
+        ]);
+
+        assert_eq!(daily_streak(&days, 4, "2026-10-16"), 2);
+        assert_eq!(daily_streak(&days, 4, "2026-10-13"), 2);
+        assert_eq!(daily_streak(&days, 4, "2026-10-14"), 2);
+        assert_eq!(daily_streak(&days, 4, "2026-10-17"), 2);
+        assert_eq!(daily_streak(&days, 4, "2026-10-18"), 0);
+    }
+
+    #[test]
+    fn today_only_counts_once_reached() {
+        let days = completed(&[("2026-10-16", 4), ("2026-10-17", 4), ("2026-10-18", 2)]);
+        assert_eq!(daily_streak(&days, 4, "2026-10-18"), 2);
+
+        let days = completed(&[("2026-10-16", 4), ("2026-10-17", 4), ("2026-10-18", 4)]);
+        assert_eq!(daily_streak(&days, 4, "2026-10-18"), 3);
+    }
+
+    #[test]
+    fn the_streak_follows_the_current_goal() {
+        let days = completed(&[("2026-10-15", 6), ("2026-10-16", 4), ("2026-10-17", 6)]);
+
+        assert_eq!(daily_streak(&days, 4, "2026-10-17"), 3);
+        // Days that only reached a lower goal don't count anymore
+        assert_eq!(daily_streak(&days, 6, "2026-10-17"), 1);
+        assert_eq!(daily_streak(&days, 7, "2026-10-17"), 0);
+    }
+
+    #[test]
+    fn weekly_streak() {
+        // Mondays of three weeks in a row, the last one being the current week
+        let days = completed(&[
+            ("2026-10-05", 10),
+            ("2026-10-12", 9),
+            ("2026-10-14", 1),
+            ("2026-10-19", 3),
+        ]);
+        let on_week = |date: NaiveDate| {
+            days.iter()
+                .filter(|(day, _)| day.iso_week() == date.iso_week())
+                .map(|(_, count)| count)
+                .sum()
+        };
+
+        assert_eq!(streak(on_week, 10, date("2026-10-21"), Days::new(7)), 2);
+    }
+
+    #[test]
+    fn reports_the_goals_once_reached() {
+        let mut goals = goals(Some(2), Some(4), "2026-10-13");
+
+        // As when loading, with a tomato done today and two earlier in the week
+        goals.count(&completed(&[("2026-10-12", 2), ("2026-10-13", 1)]), date("2026-10-13"));
+        assert_eq!((goals.today, goals.this_week, goals.daily_streak), (1, 3, 1));
+
+        let reached = goals.count(&completed(&[("2026-10-12", 2), ("2026-10-13", 2)]), date("2026-10-13"));
+        assert_eq!(kinds(reached), ["day", "week"]);
+        assert_eq!((goals.daily_streak, goals.weekly_streak), (2, 1));
+
+        // Only once
+        let reached = goals.count(&completed(&[("2026-10-12", 2), ("2026-10-13", 3)]), date("2026-10-13"));
+        assert!(reached.is_empty());
+
+        // A new day starts from zero, the week goes on
+        let reached = goals.count(
+            &completed(&[("2026-10-12", 2), ("2026-10-13", 3), ("2026-10-14", 2)]),
+            date("2026-10-14"),
+        );
+        assert_eq!(kinds(reached), ["day"]);
+        assert_eq!((goals.today, goals.this_week, goals.daily_streak), (2, 7, 3));
+    }
+}
@@ -0,0 +1,257 @@
+use std::{
+    fs::{self, OpenOptions},
+    io::{self, BufRead, BufReader, Write},
+    path::PathBuf,
+    time::Duration,
+};
+
+use chrono::{DateTime, Local, NaiveDate};
+use clap::Args;
+use owo_colors::OwoColorize;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    colors::palette,
+    error::{OrError, Result, TomateError},
+    i18n::{render, t},
+    show, showln,
+    time::Time,
+    Stage,
+};
+
+/// What happened during a single work or rest timer, accumulated while it runs.
+#[derive(Default)]
+pub struct TimerStats {
+    pub elapsed: Duration,
+    pub pauses: u32,
+    pub paused_for: Duration,
+    /// Idle time taken off `elapsed`, see [`crate::idle`].
+    pub idle: Duration,
+    /// Counted up after the tomato ended, not included in `elapsed`.
+    pub overtime: Duration,
+    pub skipped: bool,
+}
+
+impl TimerStats {
+    /// Time spent working, the overtime included, as counted by the summary and by `tomate stats`.
+    pub fn focused(&self) -> Duration {
+        self.elapsed + self.overtime
+    }
+}
+
+/// A line of the history file.
+#[derive(Serialize, Deserialize)]
+pub struct Record {
+    pub stage: Stage,
+    pub tomato: u32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub task: Option<String>,
+    pub started_at: DateTime<Local>,
+    pub ended_at: DateTime<Local>,
+    pub planned_secs: u64,
+    pub actual_secs: u64,
+    pub paused_secs: u64,
+    pub pauses: u32,
+    #[serde(default)]
+    pub idle_secs: u64,
+    #[serde(default)]
+    pub overtime_secs: u64,
+    pub skipped: bool,
+    /// Suggested for the break, see [`crate::activities`].
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub activity: Option<String>,
+    /// Whether the activity was done, `None` when there was no one to ask.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub activity_done: Option<bool>,
+}
+
+impl Record {
+    pub fn new(
+        stage: Stage,
+        tomato: u32,
+        task: Option<String>,
+        started_at: DateTime<Local>,
+        planned: Duration,
+        stats: &TimerStats,
+    ) -> Self {
+        Self {
+            stage,
+            tomato,
+            task,
+            started_at,
+            ended_at: Local::now(),
+            planned_secs: planned.as_secs(),
+            actual_secs: stats.elapsed.as_secs(),
+            paused_secs: stats.paused_for.as_secs(),
+            pauses: stats.pauses,
+            idle_secs: stats.idle.as_secs(),
+            overtime_secs: stats.overtime.as_secs(),
+            skipped: stats.skipped,
+            activity: None,
+            activity_done: None,
+        }
+    }
+
+    pub fn date(&self) -> NaiveDate {
+        self.started_at.date_naive()
+    }
+
+    /// Same as [`TimerStats::focused`].
+    pub fn focused_secs(&self) -> u64 {
+        self.actual_secs + self.overtime_secs
+    }
+}
+
+/// `$XDG_DATA_HOME/tomate/history.jsonl`, one JSON record per line.
+fn history_path() -> Result<PathBuf> {
+    Ok(dirs::data_dir()
+        .or_error(TomateError::Io, t().data_dir_missing)?
+        .join("tomate")
+        .join("history.jsonl"))
+}
+
+pub fn append(record: &Record) -> Result<()> {
+    let path = history_path()?;
+    let error_message = render(t().write_history_failed, &[("path", &path.display())]);
+
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent).or_error(TomateError::Io, &error_message)?;
+    }
+
+    let mut line = serde_json::to_string(record).or_error(TomateError::Io, &error_message)?;
+    line.push('\n');
+
+    OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&path)
+        .and_then(|mut file| file.write_all(line.as_bytes()))
+        .or_error(TomateError::Io, &error_message)
+}
+
+/// Reads all records, oldest first, skipping lines that can't be parsed.
+pub fn load() -> Result<Vec<Record>> {
+    let path = history_path()?;
+
+    let file = match fs::File::open(&path) {
+        Ok(file) => file,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(err) => {
+            return Err(err).or_error(
+                TomateError::Io,
+                &render(t().read_history_failed, &[("path", &path.display())]),
+            )
+        }
+    };
+
+    Ok(BufReader::new(file)
+        .lines()
+        .map_while(io::Result::ok)
+        .filter_map(|line| serde_json::from_str(&line).ok())
+        .collect())
+}
+
+/// How many tomatoes were completed, not skipped, on the given date.
+pub fn completed_on(date: NaiveDate) -> Result<u32> {
+    Ok(load()?
+        .iter()
+        .filter(|record| matches!(record.stage, Stage::Work) && !record.skipped && record.date() == date)
+        .count() as u32)
+}
+
+/// Filters records by the (inclusive) date range.
+#[derive(Args)]
+pub struct DateRange {
+    /// Only show records from this day onwards, as YYYY-MM-DD.
+    #[arg(long, value_name = "DATE")]
+    pub since: Option<NaiveDate>,
+    /// Only show records up to this day, as YYYY-MM-DD.
+    #[arg(long, value_name = "DATE")]
+    pub until: Option<NaiveDate>,
+}
+
+impl DateRange {
+    pub fn contains(&self, date: NaiveDate) -> bool {
+        self.since.is_none_or(|since| date >= since) && self.until.is_none_or(|until| date <= until)
+    }
+
+    pub fn load(&self) -> Result<Vec<Record>> {
+        let mut records = load()?;
+        records.retain(|record| self.contains(record.date()));
+        Ok(records)
+    }
+}
+
+#[derive(Args)]
+pub struct HistoryArgs {
+    #[command(flatten)]
+    range: DateRange,
+    /// How many of the most recent records to show.
+    #[arg(short = 'n', long, default_value_t = 20)]
+    limit: usize,
+}
+
+pub fn show_history(args: HistoryArgs) -> Result<()> {
+    let records = args.range.load()?;
+
+    if records.is_empty() {
+        showln!(t().no_records.dimmed());
+        return Ok(());
+    }
+
+    let skip = records.len().saturating_sub(args.limit);
+
+    for record in &records[skip..] {
+        let actual = Time::from(record.actual_secs as u32);
+        let planned = Time::from(record.planned_secs as u32);
+
+        show!(
+            format_args!(
+                "  {} → {}  ",
+                record.started_at.format("%Y-%m-%d %H:%M"),
+                record.ended_at.format("%H:%M"),
+            ),
+            record.stage,
+            format_args!(" {} ", record.tomato).color(palette().tomato.0),
+            format_args!(
+                " {}",
+                render(t().actual_of_planned, &[("actual", &actual), ("planned", &planned)])
+            )
+            .color(palette().time.0),
+        );
+
+        if record.pauses > 0 {
+            let paused_for = Time::from(record.paused_secs as u32);
+            show!(format_args!(
+                "  {}",
+                render(t().pauses, &[("pauses", &record.pauses), ("time", &paused_for)])
+            ));
+        }
+        if record.idle_secs > 0 {
+            let idle = Time::from(record.idle_secs as u32);
+            show!(format_args!("  {}", render(t().idle_time, &[("time", &idle)])));
+        }
+        if record.overtime_secs > 0 {
+            let overtime = Time::from(record.overtime_secs as u32);
+            show!(format_args!("  {}", render(t().overtime_done, &[("time", &overtime)])));
+        }
+        if record.skipped {
+            show!("  ", t().skipped_tag.red());
+        }
+        if let Some(task) = &record.task {
+            show!("  ", task.color(palette().task.0));
+        }
+        if let Some(activity) = &record.activity {
+            let mark = match record.activity_done {
+                Some(true) => " ✓",
+                Some(false) => " ✗",
+                None => "",
+            };
+            show!("  ", format_args!("{activity}{mark}").dimmed());
+        }
+
+        println!();
+    }
+
+    Ok(())
+}
@@ -0,0 +1,189 @@
+use std::{
+    os::unix::process::CommandExt,
+    process::{Child, Command, ExitStatus, Stdio},
+    thread::{self, JoinHandle},
+    time::{Duration, Instant},
+};
+
+use serde::Deserialize;
+
+use crate::{
+    error::{warn, OrError, OrWarn, TomateError},
+    i18n::{render, t},
+    time::{describe, HumanDuration},
+    Stage,
+};
+
+/// For the hooks without a `timeout`, also used for the `command` notifications.
+pub const DEFAULT_TIMEOUT: Duration = Duration::from_secs(10);
+const POLL_INTERVAL: Duration = Duration::from_millis(50);
+
+#[derive(Clone, Copy)]
+pub enum Event {
+    WorkStart,
+    Halfway,
+    WorkEnd,
+    RestStart,
+    RestEnd,
+    Pause,
+    Resume,
+    Skip,
+    SprintEnd,
+}
+
+impl Event {
+    /// Same as the key in the `[hooks]` table, also passed in `$TOMATE_EVENT`.
+    fn name(self) -> &'static str {
+        match self {
+            Self::WorkStart => "work_start",
+            Self::Halfway => "halfway",
+            Self::WorkEnd => "work_end",
+            Self::RestStart => "rest_start",
+            Self::RestEnd => "rest_end",
+            Self::Pause => "pause",
+            Self::Resume => "resume",
+            Self::Skip => "skip",
+            Self::SprintEnd => "sprint_end",
+        }
+    }
+}
+
+/// The `[hooks]` table of the config file, a shell command for each event.
+#[derive(Default, Deserialize)]
+#[serde(default)]
+pub struct HookConfig {
+    /// How long a hook can run before being killed.
+    pub timeout: Option<HumanDuration>,
+    pub work_start: Option<String>,
+    pub halfway: Option<String>,
+    pub work_end: Option<String>,
+    pub rest_start: Option<String>,
+    pub rest_end: Option<String>,
+    pub pause: Option<String>,
+    pub resume: Option<String>,
+    pub skip: Option<String>,
+    pub sprint_end: Option<String>,
+}
+
+impl HookConfig {
+    fn command(&self, event: Event) -> Option<&str> {
+        let command = match event {
+            Event::WorkStart => &self.work_start,
+            Event::Halfway => &self.halfway,
+            Event::WorkEnd => &self.work_end,
+            Event::RestStart => &self.rest_start,
+            Event::RestEnd => &self.rest_end,
+            Event::Pause => &self.pause,
+            Event::Resume => &self.resume,
+            Event::Skip => &self.skip,
+            Event::SprintEnd => &self.sprint_end,
+        };
+
+        command.as_deref()
+    }
+}
+
+/// Where the timer is at when the event happens, passed to the hooks as `TOMATE_*` variables.
+pub struct Context<'a> {
+    pub event: Event,
+    pub stage: Option<Stage>,
+    pub tomato: u32,
+    pub tomatoes: u32,
+    pub remaining: Duration,
+    pub task: Option<&'a str>,
+}
+
+pub struct Hooks {
+    config: HookConfig,
+    running: Vec<JoinHandle<()>>,
+}
+
+impl Hooks {
+    pub fn new(config: HookConfig) -> Self {
+        Self {
+            config,
+            running: Vec::new(),
+        }
+    }
+
+    /// Starts the hook of the event, if there's one, without waiting for it.
+    pub fn run(&mut self, context: &Context) {
+        let Some(command) = self.config.command(context.event) else {
+            return;
+        };
+
+        let mut process = Command::new("sh");
+        process
+            .arg("-c")
+            .arg(command)
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            // Its own group, so that whatever the shell started is killed along with it
+            .process_group(0)
+            .env("TOMATE_EVENT", context.event.name())
+            .env("TOMATE_TOMATO", context.tomato.to_string())
+            .env("TOMATE_TOMATOES", context.tomatoes.to_string())
+            .env("TOMATE_REMAINING", context.remaining.as_secs().to_string());
+
+        if let Some(stage) = context.stage {
+            process.env("TOMATE_STAGE", stage.name());
+        }
+        if let Some(task) = context.task {
+            process.env("TOMATE_TASK", task);
+        }
+
+        let event = context.event.name();
+
+        let Some(child) = process
+            .spawn()
+            .or_error(
+                TomateError::Io,
+                &render(t().hook_failed, &[("event", &event), ("command", &command)]),
+            )
+            .or_warn()
+        else {
+            return;
+        };
+
+        let timeout = self.config.timeout.map_or(DEFAULT_TIMEOUT, |timeout| timeout.0);
+
+        self.running.retain(|handle| !handle.is_finished());
+        self.running.push(thread::spawn(move || {
+            let mut child = child;
+
+            if wait_or_kill(&mut child, timeout).is_none() {
+                warn(&TomateError::Io(render(
+                    t().hook_timed_out,
+                    &[("event", &event), ("timeout", &describe(timeout))],
+                )));
+            }
+        }));
+    }
+
+    /// Waits for the hooks still running, so they aren't left behind when exiting.
+    pub fn wait(&mut self) {
+        for handle in self.running.drain(..) {
+            let _ = handle.join();
+        }
+    }
+}
+
+/// Waits for a child started in a process group of its own, killing the whole group if it takes longer than
+/// `timeout`, in which case there's no status.
+pub fn wait_or_kill(child: &mut Child, timeout: Duration) -> Option<ExitStatus> {
+    let deadline = Instant::now() + timeout;
+
+    // Polls, since `wait` can't time out
+    loop {
+        match child.try_wait() {
+            Ok(Some(status)) => return Some(status),
+            Ok(None) if Instant::now() < deadline => thread::sleep(POLL_INTERVAL),
+            _ => {
+                unsafe { libc::kill(-(child.id() as libc::pid_t), libc::SIGKILL) };
+                let _ = child.wait();
+                return None;
+            }
+        }
+    }
+}
@@ -0,0 +1,592 @@
+use std::{
+    env,
+    fmt::{self, Write},
+    sync::OnceLock,
+};
+
+use clap::ValueEnum;
+use serde::Deserialize;
+
+static CATALOG: OnceLock<&'static Catalog> = OnceLock::new();
+
+#[derive(Clone, Copy, ValueEnum, Deserialize)]
+pub enum Lang {
+    #[value(name = "pt-BR", alias = "pt")]
+    #[serde(rename = "pt-BR", alias = "pt")]
+    PtBr,
+    #[value(name = "en")]
+    #[serde(rename = "en")]
+    En,
+}
+
+impl Lang {
+    /// Guesses from the locale environment variables, defaulting to Portuguese.
+    pub fn detect() -> Self {
+        let locale = ["LC_ALL", "LC_MESSAGES", "LANG"]
+            .into_iter()
+            .filter_map(|var| env::var(var).ok())
+            .find(|value| !value.is_empty());
+
+        match locale {
+            Some(locale) if locale.starts_with("en") => Self::En,
+            _ => Self::PtBr,
+        }
+    }
+
+    fn catalog(self) -> &'static Catalog {
+        match self {
+            Self::PtBr => &PT_BR,
+            Self::En => &EN,
+        }
+    }
+}
+
+/// The value of `--lang`, looked up before clap parses the arguments, so its errors, like the ones of
+/// invalid durations, are already in that language.
+pub fn lang_from_args(args: impl IntoIterator<Item = String>) -> Option<Lang> {
+    let mut args = args.into_iter().skip(1);
+
+    while let Some(arg) = args.next() {
+        let value = match arg.strip_prefix("--lang") {
+            Some("") => args.next(),
+            Some(rest) => rest.strip_prefix('=').map(str::to_owned),
+            None => continue,
+        };
+
+        return value.and_then(|value| Lang::from_str(&value, true).ok());
+    }
+
+    None
+}
+
+/// Sets the language used by [`t`], can only be called once, before any output.
+pub fn set_lang(lang: Lang) {
+    let _ = CATALOG.set(lang.catalog());
+}
+
+/// The message catalog of the current language, detected from the environment until [`set_lang`] is called.
+pub fn t() -> &'static Catalog {
+    CATALOG.get().copied().unwrap_or_else(|| Lang::detect().catalog())
+}
+
+/// Replaces each `{name}` in the template by its value, unknown placeholders are kept as is. Done in a single pass,
+/// so a value with braces in it, like a task, isn't taken for a placeholder.
+pub fn render(template: &str, values: &[(&str, &dyn fmt::Display)]) -> String {
+    let mut text = String::with_capacity(template.len());
+    let mut rest = template;
+
+    while let Some(start) = rest.find('{') {
+        text.push_str(&rest[..start]);
+        rest = &rest[start..];
+
+        let placeholder = rest.find('}').and_then(|end| {
+            let (_, value) = values.iter().find(|(name, _)| *name == &rest[1..end])?;
+            Some((end, value))
+        });
+
+        match placeholder {
+            Some((end, value)) => {
+                let _ = write!(text, "{value}");
+                rest = &rest[end + 1..];
+            }
+            None => {
+                text.push('{');
+                rest = &rest[1..];
+            }
+        }
+    }
+    text.push_str(rest);
+
+    text
+}
+
+/// Every string shown to the user, with `{placeholders}` filled by [`render`].
+pub struct Catalog {
+    // Errors
+    pub error: &'static str,
+    pub warning: &'static str,
+    pub argument_zero: &'static str,
+    pub argument_too_big: &'static str,
+    pub invalid_duration: &'static str,
+    pub invalid_override_format: &'static str,
+    pub invalid_cycle_number: &'static str,
+    pub unknown_color: &'static str,
+    pub override_outside_plan: &'static str,
+    pub read_plan_failed: &'static str,
+    pub invalid_plan: &'static str,
+    pub unknown_plan_key: &'static str,
+    pub bar_connect_failed: &'static str,
+    pub bar_send_failed: &'static str,
+    pub notification_failed: &'static str,
+    pub notification_command_failed: &'static str,
+    pub notification_command_timed_out: &'static str,
+    pub notification_command_missing: &'static str,
+    pub invalid_volume: &'static str,
+    pub hook_failed: &'static str,
+    pub hook_timed_out: &'static str,
+    pub idle_command_missing: &'static str,
+    pub idle_check_failed: &'static str,
+    pub invalid_rest_ratio: &'static str,
+    pub data_dir_missing: &'static str,
+    pub config_dir_missing: &'static str,
+    pub write_history_failed: &'static str,
+    pub no_checkpoint: &'static str,
+    pub invalid_checkpoint: &'static str,
+    pub write_checkpoint_failed: &'static str,
+    pub read_history_failed: &'static str,
+    pub serialize_report_failed: &'static str,
+    pub write_report_failed: &'static str,
+    pub read_config_failed: &'static str,
+    pub invalid_config: &'static str,
+    pub unknown_config_key: &'static str,
+    pub unknown_key: &'static str,
+    pub empty_emoji_list: &'static str,
+    pub no_config_file: &'static str,
+    pub config_valid: &'static str,
+    pub socket_bind_failed: &'static str,
+    pub socket_dir_unsafe: &'static str,
+    pub daemon_already_running: &'static str,
+    pub daemon_connect_failed: &'static str,
+    pub status_file_failed: &'static str,
+    pub status_write_failed: &'static str,
+    pub team_bind_failed: &'static str,
+    pub team_connect_failed: &'static str,
+    pub invalid_goal: &'static str,
+    pub invalid_adaptive_rest: &'static str,
+    pub invalid_activity_weight: &'static str,
+    pub invalid_speed: &'static str,
+
+    // Timer
+    pub stage_work: &'static str,
+    pub stage_rest: &'static str,
+    pub progress: &'static str,
+    pub long_break_next: &'static str,
+    pub minutes: &'static str,
+    pub ask_task_keep: &'static str,
+    pub ask_task_none: &'static str,
+    pub work_started: &'static str,
+    pub resuming: &'static str,
+    pub ask_count_offline: &'static str,
+    pub hooray: &'static str,
+    pub tomato_done: &'static str,
+    pub paused: &'static str,
+    pub skipped: &'static str,
+    pub skipped_at: &'static str,
+    pub keys_help: &'static str,
+    pub session_summary: &'static str,
+    pub completed_today: &'static str,
+
+    // Overtime
+    pub resuming_overtime: &'static str,
+    pub overtime: &'static str,
+    pub overtime_help: &'static str,
+    pub overtime_done: &'static str,
+
+    // Idle
+    pub idle_paused: &'static str,
+    pub ask_discard_idle: &'static str,
+
+    // Speed and dry run
+    pub not_recording: &'static str,
+
+    // Notifications
+    pub work_start_notification: &'static str,
+    pub halfway_notification: &'static str,
+    pub overtime_notification: &'static str,
+    pub work_done_notification: &'static str,
+    pub long_work_done_notification: &'static str,
+
+    // Daemon
+    pub daemon_listening: &'static str,
+    pub daemon_idle: &'static str,
+
+    // Team
+    pub team_listening: &'static str,
+    pub team_anonymous: &'static str,
+    pub team_keys_help: &'static str,
+    pub team_work_started: &'static str,
+    pub team_rest_started: &'static str,
+    pub team_paused: &'static str,
+    pub team_resumed: &'static str,
+    pub team_skipped: &'static str,
+    pub team_ended: &'static str,
+
+    // Bar
+    pub bar_work: &'static str,
+    pub bar_rest: &'static str,
+
+    // Goals
+    pub goal_today: &'static str,
+    pub goal_week: &'static str,
+    pub daily_goal_reached: &'static str,
+    pub weekly_goal_reached: &'static str,
+
+    // Activities
+    pub default_activities: &'static [&'static str],
+    pub activity_suggestion: &'static str,
+    pub ask_activity_done: &'static str,
+
+    // Adaptive rest
+    pub adaptive_rest: &'static str,
+    pub adaptive_streak: &'static str,
+    pub adaptive_overtime: &'static str,
+    pub adaptive_skipped: &'static str,
+    pub adaptive_at_least: &'static str,
+    pub adaptive_at_most: &'static str,
+    pub adaptive_steady: &'static str,
+
+    // History and stats
+    pub no_records: &'static str,
+    pub actual_of_planned: &'static str,
+    pub pauses: &'static str,
+    pub idle_time: &'static str,
+    pub skipped_tag: &'static str,
+    pub ics_work_summary: &'static str,
+    pub ics_rest_summary: &'static str,
+    pub stats_tomatoes: &'static str,
+    pub stats_completed: &'static str,
+    pub stats_skipped: &'static str,
+    pub stats_average_pause: &'static str,
+    pub stats_longest_streak: &'static str,
+    pub days: &'static str,
+    pub per_day: &'static str,
+    pub per_week: &'static str,
+    pub per_task: &'static str,
+    pub tomato_count: &'static str,
+    pub task_count: &'static str,
+    pub no_task: &'static str,
+    pub week_label: &'static str,
+    pub weekdays: [&'static str; 7],
+}
+
+pub const PT_BR: Catalog = Catalog {
+    error: "Erro",
+    warning: "Aviso",
+    argument_zero: "o argumento {argument} não pode ser zero!",
+    argument_too_big: "o argumento {argument} não pode passar de um dia.",
+    invalid_duration: "duração inválida \"{text}\", esperava algo como 25, 1h30m, 45s ou 2.5m",
+    invalid_override_format: "esperava o formato N:TRABALHO/DESCANSO",
+    invalid_cycle_number: "número de ciclo inválido: \"{cycle}\"",
+    unknown_color: "cor desconhecida \"{name}\"",
+    override_outside_plan: "o ciclo {cycle} está fora do plano",
+    read_plan_failed: "falha ao ler o arquivo de plano em {path}",
+    invalid_plan: "arquivo de plano inválido em {path}",
+    unknown_plan_key: "chave desconhecida `{key}` no arquivo de plano em {path}",
+    bar_connect_failed: "falha ao conectar com a barra",
+    bar_send_failed: "falha ao enviar o estado para a barra, ela não será mais atualizada",
+    notification_failed: "falha ao enviar a notificação: \"{body}\"",
+    notification_command_failed: "o comando de notificação `{command}` terminou com {status}",
+    notification_command_timed_out: "o comando de notificação passou de {timeout} e foi encerrado",
+    notification_command_missing: "o backend de notificação `command` precisa da chave `command` em [notifications]",
+    invalid_volume: "o volume em [sounds] deve estar entre 0 e 1",
+    hook_failed: "falha ao rodar o hook {event}: `{command}`",
+    hook_timed_out: "o hook {event} passou de {timeout} e foi encerrado",
+    idle_command_missing: "a fonte command em [idle] precisa de um command",
+    idle_check_failed: "falha ao verificar a inatividade, o timer não vai pausar sozinho",
+    invalid_rest_ratio: "o rest_ratio em [overtime] deve estar entre 0 e 1",
+    data_dir_missing: "diretório de dados não encontrado",
+    config_dir_missing: "diretório de configuração não encontrado",
+    write_history_failed: "falha ao escrever o histórico em {path}",
+    no_checkpoint: "não há nenhuma sessão interrompida para retomar",
+    invalid_checkpoint: "a sessão salva em {path} é inválida",
+    write_checkpoint_failed: "falha ao salvar o progresso, a sessão não poderá ser retomada",
+    read_history_failed: "falha ao ler o histórico em {path}",
+    serialize_report_failed: "falha ao serializar o relatório",
+    write_report_failed: "falha ao escrever o relatório em {path}",
+    read_config_failed: "falha ao ler a configuração em {path}",
+    invalid_config: "configuração inválida em {path}:\n{error}",
+    unknown_config_key: "chave desconhecida `{key}` em {path}, veja `tomate config check`",
+    unknown_key: "chave desconhecida `{key}`",
+    empty_emoji_list: "a lista {list} não pode estar vazia!",
+    no_config_file: "Nenhum arquivo de configuração em {path}.",
+    config_valid: "{path} é válido.",
+    socket_bind_failed: "falha ao abrir o socket de controle em {path}",
+    socket_dir_unsafe: "{path} não é uma pasta só deste usuário, escolha onde fica o socket com --socket",
+    daemon_already_running: "já tem um daemon escutando em {path}",
+    daemon_connect_failed: "falha ao falar com o daemon em {path} (ele está rodando?)",
+    status_file_failed: "falha ao abrir {path} para escrever o status",
+    status_write_failed: "falha ao escrever o status, a barra não será mais atualizada",
+    team_bind_failed: "falha ao escutar em {address}",
+    team_connect_failed: "falha ao conectar em {address}",
+    invalid_goal: "as metas em [goals] devem ser de pelo menos 1 tomate",
+    invalid_adaptive_rest: "o min em [adaptive_rest] não pode ser maior que o max",
+    invalid_activity_weight: "os pesos em [break_activities] devem ser de pelo menos 1",
+    invalid_speed: "a velocidade deve ser um número positivo, como 60 ou 60x",
+
+    stage_work: "[Trabalho]",
+    stage_rest: "[Descanso]",
+    progress: "ciclo {cycle}/{cycles}",
+    long_break_next: ", pausa longa a seguir",
+    minutes: "{minutes} minutos",
+    ask_task_keep: "Tarefa deste tomate (ENTER mantém \"{task}\"): ",
+    ask_task_none: "Tarefa deste tomate (ENTER para nenhuma): ",
+    work_started: "Tomate de {duration}{task} iniciado!",
+    resuming: "{stage} Retomando o tomate {tomato}, faltando {time}",
+    ask_count_offline: "O tomate ficou fechado por {time}, contar esse tempo? [s/N] ",
+    hooray: "[Eba!]",
+    tomato_done: "Tomate {tomato} concluído! Sua recompensa: {emoji}",
+    paused: "(Pausado)",
+    skipped: "pulado",
+    skipped_at: "{skipped} em {time}.",
+    keys_help: "espaço pausa · s pula · +/- um minuto · r reinicia · q sai",
+    session_summary: "Sessão encerrada: {completed} tomate(s) concluído(s), {skipped} pulado(s), {focused} de foco e {paused} em pausa.",
+    completed_today: "{count} concluído(s) hoje",
+
+    resuming_overtime: "{stage} Retomando a hora extra do tomate {tomato}, em +{time}",
+    overtime: "hora extra",
+    overtime_help: "espaço ou s descansa · q sai",
+    overtime_done: "{time} de hora extra",
+
+    idle_paused: "pausado sozinho, inativo há {idle}",
+    ask_discard_idle: "Descartar os {idle} inativos deste tomate? [s/N] ",
+
+    not_recording: "Tempo simulado, nada vai para o histórico.",
+
+    work_start_notification: "Iniciando tomate de {duration}{task}! ({progress})",
+    halfway_notification: "Na metade! Você está focado, não está? {emoji}",
+    overtime_notification: "Tempo! Tomate {tomato}/{cycles}{task} acabou, continue no fluxo ou vá descansar.",
+    work_done_notification: "Tomate {tomato}/{cycles}{task} concluído! {emoji} Descanse {duration}.",
+    long_work_done_notification:
+        "Tomate {tomato}/{cycles}{task} concluído! {emoji} Faça uma pausa longa de {duration}.",
+
+    daemon_listening: "Escutando em {path}",
+    daemon_idle: "Nenhum timer rodando.",
+
+    team_listening: "Sessão aberta em {address}, entre com `tomate join`",
+    team_anonymous: "anônimo",
+    team_keys_help: "espaço pausa · s pula · q sai da sessão",
+    team_work_started: "Hora de trabalhar, um tomate começou na sessão do time.",
+    team_rest_started: "Hora de descansar, o tomate da sessão do time acabou.",
+    team_paused: "{name} pausou",
+    team_resumed: "{name} retomou",
+    team_skipped: "{name} pulou",
+    team_ended: "A sessão terminou.",
+
+    bar_work: "trabalho",
+    bar_rest: "descanso",
+
+    goal_today: "{count}/{goal} hoje",
+    goal_week: "{count}/{goal} na semana",
+    daily_goal_reached: "🎯 Meta do dia batida, {goal} tomates! Sequência de {streak} dia(s).",
+    weekly_goal_reached: "🏆 Meta da semana batida, {goal} tomates! Sequência de {streak} semana(s).",
+
+    default_activities: &[
+        "Alongar o pescoço e os ombros",
+        "Beber um copo d'água",
+        "Olhar para algo a 6 metros por 20 segundos",
+        "Dar uma volta",
+        "Respirar fundo algumas vezes",
+    ],
+    activity_suggestion: "Sugestão para o descanso: {activity}",
+    ask_activity_done: "Fez \"{activity}\"? [s/N] ",
+
+    adaptive_rest: "Descanso de {duration}: {reasons}",
+    adaptive_streak: "{count} tomates seguidos sem pausa (+{time})",
+    adaptive_overtime: "hora extra (+{time})",
+    adaptive_skipped: "tomate pulado (-{time})",
+    adaptive_at_least: "no mínimo {time}",
+    adaptive_at_most: "no máximo {time}",
+    adaptive_steady: "como planejado",
+
+    no_records: "Nenhum tomate registrado.",
+    actual_of_planned: "{actual} de {planned}",
+    pauses: "{pauses} pausa(s), {time}",
+    idle_time: "{time} inativo",
+    skipped_tag: "(pulado)",
+    ics_work_summary: "Tomate {tomato}",
+    ics_rest_summary: "Descanso",
+    stats_tomatoes: "Tomates: {completed}, {skipped} ({ratio}% concluídos)",
+    stats_completed: "{count} concluídos",
+    stats_skipped: "{count} pulados",
+    stats_average_pause: "Pausa média: {time}",
+    stats_longest_streak: "Maior sequência: {days}",
+    days: "{count} dia(s)",
+    per_day: "Por dia:",
+    per_week: "Por semana:",
+    per_task: "Por tarefa:",
+    tomato_count: "({count} tomate(s))",
+    task_count: "({completed} tomate(s), {skipped} pulado(s))",
+    no_task: "(sem tarefa)",
+    week_label: "{year}-S{week}",
+    weekdays: ["Seg", "Ter", "Qua", "Qui", "Sex", "Sáb", "Dom"],
+};
+
+pub const EN: Catalog = Catalog {
+    error: "Error",
+    warning: "Warning",
+    argument_zero: "the {argument} argument can't be zero!",
+    argument_too_big: "the {argument} argument cannot be longer than a day.",
+    invalid_duration: "invalid duration \"{text}\", expected something like 25, 1h30m, 45s or 2.5m",
+    invalid_override_format: "expected the format N:WORK/REST",
+    invalid_cycle_number: "invalid cycle number: \"{cycle}\"",
+    unknown_color: "unknown color \"{name}\"",
+    override_outside_plan: "cycle override {cycle} is outside of the plan",
+    read_plan_failed: "failed to read plan file at {path}",
+    invalid_plan: "invalid plan file at {path}",
+    unknown_plan_key: "unknown key `{key}` in plan file at {path}",
+    bar_connect_failed: "failed to connect bar messager",
+    bar_send_failed: "failed to send the state to the bar, it won't be updated anymore",
+    notification_failed: "failed to send notification with body: \"{body}\"",
+    notification_command_failed: "the notification command `{command}` exited with {status}",
+    notification_command_timed_out: "the notification command took longer than {timeout} and was killed",
+    notification_command_missing: "the `command` notification backend needs the `command` key in [notifications]",
+    invalid_volume: "the volume in [sounds] must be between 0 and 1",
+    hook_failed: "failed to run the {event} hook: `{command}`",
+    hook_timed_out: "the {event} hook took longer than {timeout} and was killed",
+    idle_command_missing: "the command source in [idle] needs a command",
+    idle_check_failed: "failed to check for idle time, the timer won't pause by itself",
+    invalid_rest_ratio: "the rest_ratio in [overtime] must be between 0 and 1",
+    data_dir_missing: "failed to find the data directory",
+    config_dir_missing: "failed to find the config directory",
+    write_history_failed: "failed to write history at {path}",
+    no_checkpoint: "there's no interrupted session to resume",
+    invalid_checkpoint: "the session saved at {path} is invalid",
+    write_checkpoint_failed: "failed to save the progress, the session can't be resumed",
+    read_history_failed: "failed to read history at {path}",
+    serialize_report_failed: "failed to serialize the report",
+    write_report_failed: "failed to write report to {path}",
+    read_config_failed: "failed to read config at {path}",
+    invalid_config: "invalid config at {path}:\n{error}",
+    unknown_config_key: "unknown key `{key}` in {path}, see `tomate config check`",
+    unknown_key: "unknown key `{key}`",
+    empty_emoji_list: "the {list} list can't be empty!",
+    no_config_file: "No config file at {path}.",
+    config_valid: "{path} is valid.",
+    socket_bind_failed: "failed to open the control socket at {path}",
+    socket_dir_unsafe: "{path} isn't a directory of this user only, choose where the socket goes with --socket",
+    daemon_already_running: "a daemon is already listening at {path}",
+    daemon_connect_failed: "failed to talk to the daemon at {path} (is it running?)",
+    status_file_failed: "failed to open {path} to write the status",
+    status_write_failed: "failed to write the status, the bar won't be updated anymore",
+    team_bind_failed: "failed to listen at {address}",
+    team_connect_failed: "failed to connect to {address}",
+    invalid_goal: "the goals in [goals] must be of at least 1 tomato",
+    invalid_adaptive_rest: "the min in [adaptive_rest] can't be greater than the max",
+    invalid_activity_weight: "the weights in [break_activities] must be at least 1",
+    invalid_speed: "the speed must be a positive number, like 60 or 60x",
+
+    stage_work: "[Work]",
+    stage_rest: "[Rest]",
+    progress: "cycle {cycle}/{cycles}",
+    long_break_next: ", long break next",
+    minutes: "{minutes} minutes",
+    ask_task_keep: "Task for this tomato (ENTER keeps \"{task}\"): ",
+    ask_task_none: "Task for this tomato (ENTER for none): ",
+    work_started: "Started a tomato of {duration}{task}!",
+    resuming: "{stage} Resuming tomato {tomato}, {time} left",
+    ask_count_offline: "Tomate was closed for {time}, count that time? [y/N] ",
+    hooray: "[Yay!]",
+    tomato_done: "Tomato {tomato} done! Your reward: {emoji}",
+    paused: "(Paused)",
+    skipped: "skipped",
+    skipped_at: "{skipped} at {time}.",
+    keys_help: "space pause · s skip · +/- a minute · r restart · q quit",
+    session_summary:
+        "Session ended: {completed} tomato(es) done, {skipped} skipped, {focused} focused and {paused} paused.",
+    completed_today: "{count} done today",
+
+    resuming_overtime: "{stage} Resuming the overtime of tomato {tomato}, at +{time}",
+    overtime: "overtime",
+    overtime_help: "space or s takes the break · q quits",
+    overtime_done: "{time} of overtime",
+
+    idle_paused: "paused by itself, idle for {idle}",
+    ask_discard_idle: "Discard the {idle} spent idle from this tomato? [y/N] ",
+
+    not_recording: "Simulated time, nothing goes to the history.",
+
+    work_start_notification: "Starting a tomato of {duration}{task}! ({progress})",
+    halfway_notification: "Halfway there! You're focused, aren't you? {emoji}",
+    overtime_notification: "Time! Tomato {tomato}/{cycles}{task} is over, keep the flow or go take a break.",
+    work_done_notification: "Tomato {tomato}/{cycles}{task} done! {emoji} Rest for {duration}.",
+    long_work_done_notification: "Tomato {tomato}/{cycles}{task} done! {emoji} Take a long break of {duration}.",
+
+    daemon_listening: "Listening at {path}",
+    daemon_idle: "No timer running.",
+
+    team_listening: "Session open at {address}, join it with `tomate join`",
+    team_anonymous: "anonymous",
+    team_keys_help: "space pause · s skip · q leave the session",
+    team_work_started: "Time to work, a tomato started in the team session.",
+    team_rest_started: "Time to rest, the tomato of the team session is over.",
+    team_paused: "{name} paused",
+    team_resumed: "{name} resumed",
+    team_skipped: "{name} skipped",
+    team_ended: "The session is over.",
+
+    bar_work: "work",
+    bar_rest: "rest",
+
+    goal_today: "{count}/{goal} today",
+    goal_week: "{count}/{goal} this week",
+    daily_goal_reached: "🎯 Daily goal reached, {goal} tomatoes! {streak} day(s) in a row.",
+    weekly_goal_reached: "🏆 Weekly goal reached, {goal} tomatoes! {streak} week(s) in a row.",
+
+    default_activities: &[
+        "Stretch your neck and shoulders",
+        "Drink a glass of water",
+        "Look at something 20 feet away for 20 seconds",
+        "Take a walk",
+        "Take a few deep breaths",
+    ],
+    activity_suggestion: "Suggestion for the break: {activity}",
+    ask_activity_done: "Done with \"{activity}\"? [y/N] ",
+
+    adaptive_rest: "A break of {duration}: {reasons}",
+    adaptive_streak: "{count} tomatoes in a row without pauses (+{time})",
+    adaptive_overtime: "overtime (+{time})",
+    adaptive_skipped: "skipped tomato (-{time})",
+    adaptive_at_least: "at least {time}",
+    adaptive_at_most: "at most {time}",
+    adaptive_steady: "as planned",
+
+    no_records: "No tomatoes recorded.",
+    actual_of_planned: "{actual} of {planned}",
+    pauses: "{pauses} pause(s), {time}",
+    idle_time: "{time} idle",
+    skipped_tag: "(skipped)",
+    ics_work_summary: "Tomato {tomato}",
+    ics_rest_summary: "Break",
+    stats_tomatoes: "Tomatoes: {completed}, {skipped} ({ratio}% completed)",
+    stats_completed: "{count} completed",
+    stats_skipped: "{count} skipped",
+    stats_average_pause: "Average pause: {time}",
+    stats_longest_streak: "Longest streak: {days}",
+    days: "{count} day(s)",
+    per_day: "Per day:",
+    per_week: "Per week:",
+    per_task: "Per task:",
+    tomato_count: "({count} tomato(es))",
+    task_count: "({completed} tomato(es), {skipped} skipped)",
+    no_task: "(no task)",
+    week_label: "{year}-W{week}",
+    weekdays: ["Mon", "Tue", "Wed", "Thu", "Fri", "Sat", "Sun"],
+};
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn fills_the_placeholders() {
+        let text = render(
+            "Tomato {tomato}/{cycles}{task}",
+            &[("tomato", &2), ("cycles", &4), ("task", &": docs")],
+        );
+
+        assert_eq!(text, "Tomato 2/4: docs");
+    }
+
+    #[test]
+    fn keeps_unknown_placeholders_and_lone_braces() {
+        assert_eq!(render("{unknown} {tomato}", &[("tomato", &1)]), "{unknown} 1");
+        assert_eq!(render("{ {{tomato}} }", &[("tomato", &1)]), "{ {1} }");
+    }
+
+    #[test]
+    fn leaves_the_values_alone() {
+        let text = render("{task} in cycle {cycle}", &[("task", &"fix {cycle}"), ("cycle", &3)]);
+
+        assert_eq!(text, "fix {cycle} in cycle 3");
+    }
+}
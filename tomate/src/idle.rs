@@ -0,0 +1,381 @@
+use std::{
+    env,
+    io::Read,
+    mem::MaybeUninit,
+    os::unix::process::CommandExt,
+    process::{Command, Stdio},
+    sync::mpsc::{self, Receiver, TryRecvError},
+    thread,
+    time::{Duration, Instant},
+};
+
+use serde::Deserialize;
+
+use crate::{
+    error::{warn, TomateError},
+    hooks::wait_or_kill,
+    i18n::t,
+    time::HumanDuration,
+};
+
+const DEFAULT_THRESHOLD: Duration = Duration::from_secs(5 * 60);
+/// Checking spawns a process, no need to do it at every tick.
+const POLL_INTERVAL: Duration = Duration::from_secs(5);
+/// A check taking longer than this is killed, and the idle time of the last one is kept.
+const PROBE_TIMEOUT: Duration = Duration::from_secs(2);
+
+/// Where to find out for how long the user hasn't touched the computer.
+#[derive(Clone, Copy, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum IdleSource {
+    /// The `IdleHint` and `LockedHint` of the session, through `loginctl`.
+    #[default]
+    Logind,
+    /// A command printing the idle milliseconds, like `xprintidle`.
+    Command,
+}
+
+/// What to do with the idle time counted before the timer paused by itself.
+#[derive(Clone, Copy, Default, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum DiscardIdle {
+    /// Ask when resuming, or discard it if there's no terminal to ask on.
+    #[default]
+    Ask,
+    Always,
+    Never,
+}
+
+/// The `[idle]` table of the config file.
+#[derive(Default, Deserialize)]
+#[serde(default)]
+pub struct IdleConfig {
+    pub enabled: bool,
+    /// How long without input before pausing a tomato.
+    pub threshold: Option<HumanDuration>,
+    pub source: IdleSource,
+    pub command: Option<String>,
+    pub discard: DiscardIdle,
+}
+
+impl IdleConfig {
+    /// Whether the `command` source has a command to run.
+    pub fn is_valid(&self) -> bool {
+        !matches!(self.source, IdleSource::Command) || self.command.is_some()
+    }
+}
+
+/// What a check running on its own thread found.
+enum Probe {
+    Idle(Duration),
+    Failed,
+    TimedOut,
+}
+
+pub struct IdleDetector {
+    source: IdleSource,
+    command: Option<String>,
+    threshold: Duration,
+    pub discard: DiscardIdle,
+    checked_at: Option<Instant>,
+    /// The check started at `checked_at`, until it's done.
+    probe: Option<Receiver<Probe>>,
+    /// Found by the last check that didn't time out, forgotten when postponing.
+    idle: Option<Duration>,
+    /// Set after the first failure, warning once is enough.
+    failed: bool,
+}
+
+impl IdleDetector {
+    /// `None` unless enabled in the config.
+    pub fn new(config: IdleConfig) -> Option<Self> {
+        config.enabled.then(|| Self {
+            source: config.source,
+            command: config.command,
+            threshold: config.threshold.map_or(DEFAULT_THRESHOLD, |threshold| threshold.0),
+            discard: config.discard,
+            checked_at: None,
+            probe: None,
+            idle: None,
+            failed: false,
+        })
+    }
+
+    /// For how long the user has been idle, once past the threshold. Only checks every few seconds, on a
+    /// thread of its own so a hanging command doesn't stop the timer, returning `None` until it's done.
+    pub fn check(&mut self) -> Option<Duration> {
+        if self.failed {
+            return None;
+        }
+
+        let Some(probe) = &self.probe else {
+            if self
+                .checked_at
+                .is_none_or(|checked_at| checked_at.elapsed() >= POLL_INTERVAL)
+            {
+                self.checked_at = Some(Instant::now());
+                self.probe = Some(self.spawn_probe());
+            }
+            return None;
+        };
+
+        match probe.try_recv() {
+            Ok(Probe::Idle(idle)) => self.idle = Some(idle),
+            Ok(Probe::TimedOut) => {}
+            Ok(Probe::Failed) | Err(TryRecvError::Disconnected) => {
+                self.probe = None;
+                self.failed = true;
+                warn(&TomateError::Io(t().idle_check_failed.to_owned()));
+                return None;
+            }
+            Err(TryRecvError::Empty) => return None,
+        }
+        self.probe = None;
+
+        self.idle.filter(|idle| *idle >= self.threshold)
+    }
+
+    /// Waits a whole interval before checking again, so a stale hint doesn't pause the timer right
+    /// after resuming it.
+    pub fn postpone(&mut self) {
+        self.checked_at = Some(Instant::now());
+        self.probe = None;
+        self.idle = None;
+    }
+
+    fn spawn_probe(&self) -> Receiver<Probe> {
+        let mut process = match self.source {
+            IdleSource::Logind => {
+                let session = env::var("XDG_SESSION_ID").unwrap_or_else(|_| "auto".to_owned());
+                let mut process = Command::new("loginctl");
+                process.args(["show-session", &session]).args([
+                    "--property=IdleHint",
+                    "--property=IdleSinceHintMonotonic",
+                    "--property=LockedHint",
+                ]);
+                process
+            }
+            IdleSource::Command => {
+                let mut process = Command::new("sh");
+                process.arg("-c").arg(self.command.as_deref().unwrap_or_default());
+                process
+            }
+        };
+        process
+            .stdin(Stdio::null())
+            .stdout(Stdio::piped())
+            .stderr(Stdio::null())
+            // A hanging check is killed along with whatever it started, see `wait_or_kill`
+            .process_group(0);
+
+        let (source, threshold) = (self.source, self.threshold);
+        let (sender, receiver) = mpsc::channel();
+
+        thread::spawn(move || {
+            let _ = sender.send(probe(process, source, threshold));
+        });
+
+        receiver
+    }
+}
+
+fn probe(mut process: Command, source: IdleSource, threshold: Duration) -> Probe {
+    let Ok(mut child) = process.spawn() else {
+        return Probe::Failed;
+    };
+
+    match wait_or_kill(&mut child, PROBE_TIMEOUT) {
+        Some(status) if status.success() => {}
+        Some(_) => return Probe::Failed,
+        None => return Probe::TimedOut,
+    }
+
+    let mut output = String::new();
+    if child.stdout.take().unwrap().read_to_string(&mut output).is_err() {
+        return Probe::Failed;
+    }
+
+    let idle = match source {
+        IdleSource::Logind => session_idle(&output, monotonic_now(), threshold),
+        IdleSource::Command => command_idle(&output),
+    };
+
+    idle.map_or(Probe::Failed, Probe::Idle)
+}
+
+/// The idle time from the output of `loginctl show-session`, `now` being on the monotonic clock.
+fn session_idle(output: &str, now: Duration, threshold: Duration) -> Option<Duration> {
+    let property = |name: &str| {
+        output
+            .lines()
+            .find_map(|line| line.strip_prefix(name)?.strip_prefix('='))
+    };
+
+    let idle = property("IdleHint")? == "yes";
+    let locked = property("LockedHint") == Some("yes");
+    let since = Duration::from_micros(property("IdleSinceHintMonotonic")?.parse().ok()?);
+
+    let idle_for = if idle && !since.is_zero() {
+        now.saturating_sub(since)
+    } else {
+        Duration::ZERO
+    };
+
+    Some(if locked { idle_for.max(threshold) } else { idle_for })
+}
+
+/// Same clock as the `*Monotonic` properties of logind.
+fn monotonic_now() -> Duration {
+    let mut now = MaybeUninit::<libc::timespec>::uninit();
+    unsafe { libc::clock_gettime(libc::CLOCK_MONOTONIC, now.as_mut_ptr()) };
+    let now = unsafe { now.assume_init() };
+
+    Duration::new(now.tv_sec as u64, now.tv_nsec as u32)
+}
+
+/// The idle time from the output of the `command` source, in milliseconds.
+fn command_idle(output: &str) -> Option<Duration> {
+    let millis = output.trim().parse().ok()?;
+
+    Some(Duration::from_millis(millis))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MINUTE: Duration = Duration::from_secs(60);
+
+    fn loginctl(idle: &str, since_micros: u64, locked: &str) -> String {
+        format!("IdleHint={idle}\nIdleSinceHintMonotonic={since_micros}\nLockedHint={locked}\n")
+    }
+
+    #[test]
+    fn idle_since_the_hint() {
+        let output = loginctl("yes", 60_000_000, "no");
+
+        assert_eq!(session_idle(&output, 11 * MINUTE, 5 * MINUTE), Some(10 * MINUTE));
+    }
+
+    #[test]
+    fn not_idle_without_the_hint() {
+        let output = loginctl("no", 60_000_000, "no");
+        assert_eq!(session_idle(&output, 11 * MINUTE, 5 * MINUTE), Some(Duration::ZERO));
+
+        // Some sessions set the hint without saying since when
+        let output = loginctl("yes", 0, "no");
+        assert_eq!(session_idle(&output, 11 * MINUTE, 5 * MINUTE), Some(Duration::ZERO));
+    }
+
+    #[test]
+    fn locked_counts_as_idle() {
+        let output = loginctl("no", 0, "yes");
+        assert_eq!(session_idle(&output, 11 * MINUTE, 5 * MINUTE), Some(5 * MINUTE));
+
+        let output = loginctl("yes", 60_000_000, "yes");
+        assert_eq!(session_idle(&output, 11 * MINUTE, 5 * MINUTE), Some(10 * MINUTE));
+    }
+
+    #[test]
+    fn unexpected_output_is_a_failure() {
+        assert_eq!(session_idle("", MINUTE, MINUTE), None);
+        assert_eq!(session_idle("IdleHint=yes\n", MINUTE, MINUTE), None);
+        assert_eq!(
+            session_idle("IdleHint=yes\nIdleSinceHintMonotonic=soon\n", MINUTE, MINUTE),
+            None
+        );
+    }
+
+    fn command_detector(command: &str, threshold: Duration) -> IdleDetector {
+        IdleDetector::new(IdleConfig {
+            enabled: true,
+            threshold: Some(HumanDuration(threshold)),
+            source: IdleSource::Command,
+            command: Some(command.to_owned()),
+            discard: DiscardIdle::Always,
+        })
+        .unwrap()
+    }
+
+    /// As if the last check was a whole interval ago.
+    fn wait_interval(detector: &mut IdleDetector) {
+        detector.checked_at = detector.checked_at.map(|checked_at| checked_at - POLL_INTERVAL);
+    }
+
+    /// Checks like the timer does at every tick, until the check started, if any, is done.
+    fn check(detector: &mut IdleDetector) -> Option<Duration> {
+        let started_at = Instant::now();
+
+        loop {
+            let idle = detector.check();
+            if detector.probe.is_none() {
+                return idle;
+            }
+
+            assert!(started_at.elapsed() < PROBE_TIMEOUT * 2, "the check never ended");
+            thread::sleep(Duration::from_millis(10));
+        }
+    }
+
+    #[test]
+    fn only_past_the_threshold() {
+        let mut detector = command_detector("echo 240000", 5 * MINUTE);
+        assert_eq!(check(&mut detector), None);
+
+        let mut detector = command_detector("echo 360000", 5 * MINUTE);
+        assert_eq!(check(&mut detector), Some(6 * MINUTE));
+    }
+
+    #[test]
+    fn checks_once_per_interval() {
+        let mut detector = command_detector("echo 360000", 5 * MINUTE);
+
+        assert_eq!(check(&mut detector), Some(6 * MINUTE));
+        assert_eq!(check(&mut detector), None);
+
+        wait_interval(&mut detector);
+        assert_eq!(check(&mut detector), Some(6 * MINUTE));
+
+        // A whole interval again after resuming
+        wait_interval(&mut detector);
+        detector.postpone();
+        assert_eq!(check(&mut detector), None);
+    }
+
+    #[test]
+    fn stops_checking_after_failing() {
+        let mut detector = command_detector("exit 1", 5 * MINUTE);
+        assert_eq!(check(&mut detector), None);
+        assert!(detector.failed);
+
+        detector.command = Some("echo 360000".to_owned());
+        wait_interval(&mut detector);
+        assert_eq!(check(&mut detector), None);
+    }
+
+    #[test]
+    fn never_waits_for_the_command() {
+        let mut detector = command_detector("sleep 10; echo 360000", 5 * MINUTE);
+
+        let started_at = Instant::now();
+        assert_eq!(detector.check(), None);
+        assert_eq!(detector.check(), None);
+        assert!(started_at.elapsed() < Duration::from_millis(500));
+    }
+
+    #[test]
+    fn keeps_the_last_idle_time_when_timing_out() {
+        let mut detector = command_detector("echo 360000", 5 * MINUTE);
+        assert_eq!(check(&mut detector), Some(6 * MINUTE));
+
+        detector.command = Some("sleep 10; echo 0".to_owned());
+        wait_interval(&mut detector);
+        assert_eq!(check(&mut detector), Some(6 * MINUTE));
+        assert!(!detector.failed);
+
+        // Until the user is back
+        detector.postpone();
+        wait_interval(&mut detector);
+        assert_eq!(check(&mut detector), None);
+    }
+}
@@ -0,0 +1,68 @@
+use std::{
+    io::{self, BufRead, BufReader, Read, Write},
+    iter,
+    net::{TcpListener, TcpStream},
+    os::unix::net::{UnixListener, UnixStream},
+    thread,
+};
+
+use serde::{de::DeserializeOwned, Serialize};
+
+/// Where connections come from, the control socket of the daemon or the port of a team host.
+pub trait Listener: Send + 'static {
+    type Stream: Send + 'static;
+
+    fn accept_stream(&self) -> io::Result<Self::Stream>;
+}
+
+impl Listener for UnixListener {
+    type Stream = UnixStream;
+
+    fn accept_stream(&self) -> io::Result<UnixStream> {
+        self.accept().map(|(stream, _)| stream)
+    }
+}
+
+impl Listener for TcpListener {
+    type Stream = TcpStream;
+
+    fn accept_stream(&self) -> io::Result<TcpStream> {
+        self.accept().map(|(stream, _)| stream)
+    }
+}
+
+/// Accepts connections in the background, each handled on a thread of its own, so one that never writes doesn't
+/// keep the others waiting. The handler also gets an id, counting from 0, to tell the connections apart.
+pub fn serve<L: Listener>(listener: L, handle: impl Fn(u64, L::Stream) -> io::Result<()> + Clone + Send + 'static) {
+    thread::spawn(move || {
+        for (id, stream) in iter::repeat_with(|| listener.accept_stream()).flatten().enumerate() {
+            let handle = handle.clone();
+
+            // A connection hanging up early only concerns itself
+            thread::spawn(move || {
+                let _ = handle(id as u64, stream);
+            });
+        }
+    });
+}
+
+/// Parses every line read until the other end hangs up, passing the ones that aren't valid JSON as errors.
+pub fn read_lines<T: DeserializeOwned>(
+    stream: impl Read,
+    mut handle: impl FnMut(serde_json::Result<T>) -> io::Result<()>,
+) -> io::Result<()> {
+    let mut reader = BufReader::new(stream);
+    let mut line = String::new();
+
+    while reader.read_line(&mut line)? != 0 {
+        handle(serde_json::from_str(&line))?;
+        line.clear();
+    }
+
+    Ok(())
+}
+
+/// Writes the message as a single line of JSON.
+pub fn write_line(mut writer: impl Write, message: &impl Serialize) -> io::Result<()> {
+    writeln!(writer, "{}", serde_json::to_string(message)?)
+}
@@ -1,232 +1,1708 @@
+mod activities;
+mod adaptive_rest;
 mod bar_integration;
+mod calendar;
+mod checkpoint;
 mod colors;
+mod config;
+mod control;
 mod error;
+mod goals;
+mod history;
+mod hooks;
+mod i18n;
+mod idle;
+mod json_lines;
 mod nightly;
 mod notification;
+mod plan;
+mod sound;
+mod stats;
+mod status_output;
 mod stdin;
+mod team;
+mod terminal;
 mod time;
+mod timer;
+mod tui;
 
 const CLEAR_LINE: &str = "\x1B[2K";
 
 use std::{
-    fmt, io,
-    io::Write,
+    env, fmt, fs, io,
+    io::{IsTerminal, Write},
+    path::PathBuf,
+    process::ExitCode,
+    rc::Rc,
     sync::mpsc::Receiver,
     time::{Duration, Instant},
 };
 
-use clap::Parser;
+use chrono::{DateTime, Local};
+use clap::{Args, Parser, Subcommand};
 use owo_colors::OwoColorize;
+use serde::{Deserialize, Serialize};
 
 use crate::{
+    activities::ActivityPicker,
+    adaptive_rest::AdaptiveRest,
     bar_integration::{BarMessage, BarMessager},
-    error::UnwrapOrExplode,
+    calendar::ExportArgs,
+    checkpoint::{Checkpoint, OfflineTime},
+    colors::palette,
+    config::{Config, ConfigCommand, Messages},
+    control::{Control, CtlArgs, Input, SharedStatus, SocketArgs, Status},
+    error::{warn, OrError, OrWarn, Result, TomateError},
+    goals::{Goals, Period},
+    history::{HistoryArgs, Record, TimerStats},
+    hooks::{Event, HookConfig, Hooks},
+    i18n::{render, t, Lang},
+    idle::{DiscardIdle, IdleDetector},
     nightly::recv_deadline,
-    notification::send_notification,
+    notification::{Bell, Chain, Desktop, Notifier},
+    plan::{Cycle, CycleOverride, PlanFile, Progress, SessionPlan},
+    sound::{Cue, Sounds},
+    stats::StatsArgs,
+    status_output::{StatusArgs, StatusWriter},
     stdin::spawn_stdin_channel,
-    time::Time,
+    team::{HostArgs, JoinArgs, Participants},
+    time::{describe, HumanDuration, Time, MINUTE},
+    timer::{Clock, ScaledClock, SimulatedClock, SystemClock, Timer, TimerEvent, TimerState},
 };
 
-const MINUTE: Duration = Duration::from_secs(60);
-
 #[derive(Parser)]
 struct CliArgs {
-    work_time: Option<u32>,
-    rest_time: Option<u32>,
+    #[command(subcommand)]
+    command: Option<Command>,
+    #[command(flatten)]
+    session: SessionArgs,
+    /// Ask for the task on STDIN at the start of each tomato.
+    #[arg(long)]
+    ask_task: bool,
+    /// Full screen timer with a big clock and a progress bar, instead of a single line.
+    #[arg(long, global = true)]
+    tui: bool,
+    /// Language of the messages, detected from `LANG` by default.
+    #[arg(long, global = true, value_enum)]
+    lang: Option<Lang>,
+}
+
+/// How the session is planned, shared by the timer in the terminal and the daemon.
+#[derive(Args)]
+struct SessionArgs {
+    /// Duration of each tomato, like `25`, `1h30m`, `45s` or `2.5m`, bare numbers are minutes.
+    work_time: Option<HumanDuration>,
+    /// Duration of the breaks, in the same format.
+    rest_time: Option<HumanDuration>,
+    /// How many tomatoes the session has.
+    #[arg(short, long)]
+    cycles: Option<u32>,
+    /// Take a long break after every N tomatoes.
+    #[arg(long, value_name = "N")]
+    long_break_every: Option<u32>,
+    /// Duration of the long break, like `15` or `1h`.
+    #[arg(long, value_name = "DURATION")]
+    long_rest_time: Option<HumanDuration>,
+    /// Override the durations of a single cycle, like `3:50/10`, `3:50` or `3:/10`.
+    #[arg(long = "cycle", value_name = "N:WORK/REST")]
+    cycle_overrides: Vec<CycleOverride>,
+    /// TOML file describing the session, flags take precedence over it.
+    #[arg(long, value_name = "FILE")]
+    plan: Option<PathBuf>,
+    /// What the tomatoes are for, shown in the timer and stored in the history.
+    #[arg(short, long, value_name = "LABEL")]
+    task: Option<String>,
+    /// Don't play sounds, see the `[sounds]` table of the config for finer control.
+    #[arg(long)]
+    silent: bool,
+    /// Lengthen or shorten the breaks depending on how the tomatoes went, see the `[adaptive_rest]` table of the config.
+    #[arg(long)]
+    adaptive_rest: bool,
+    /// Tomatoes to be done every day, see the `[goals]` table of the config.
+    #[arg(long, value_name = "N", value_parser = clap::value_parser!(u32).range(1..))]
+    daily_goal: Option<u32>,
+    /// Tomatoes to be done every week, from Monday to Sunday.
+    #[arg(long, value_name = "N", value_parser = clap::value_parser!(u32).range(1..))]
+    weekly_goal: Option<u32>,
+    /// Keep counting up when a tomato ends, until the break is taken, see the `[overtime]` table of the config.
+    #[arg(long)]
+    overtime: bool,
+    /// Run the timers this many times faster, like `60` or `60x`, without recording anything.
+    #[arg(long, value_name = "FACTOR", value_parser = parse_speed, conflicts_with = "dry_run")]
+    speed: Option<f64>,
+    /// Run the whole sprint right away with a simulated clock, without recording anything.
+    #[arg(long)]
+    dry_run: bool,
+    #[command(flatten)]
+    status: StatusArgs,
+    /// Continue the session interrupted by a crash or a closed terminal, counting the time offline or not.
+    #[arg(long, value_enum, value_name = "OFFLINE", num_args = 0..=1, default_missing_value = "ask")]
+    resume: Option<OfflineTime>,
+}
+
+/// A speed factor, with an optional `x` after it.
+fn parse_speed(text: &str) -> Result<f64, String> {
+    let factor: f64 = text
+        .strip_suffix('x')
+        .unwrap_or(text)
+        .parse()
+        .map_err(|_| t().invalid_speed.to_owned())?;
+
+    (factor.is_finite() && factor > 0.0)
+        .then_some(factor)
+        .ok_or_else(|| t().invalid_speed.to_owned())
+}
+
+#[derive(Args)]
+struct DaemonArgs {
+    #[command(flatten)]
+    session: SessionArgs,
+    #[command(flatten)]
+    socket: SocketArgs,
+}
+
+#[derive(Args)]
+struct TeamHostArgs {
+    #[command(flatten)]
+    session: SessionArgs,
+    #[command(flatten)]
+    team: HostArgs,
+}
+
+#[derive(Subcommand)]
+enum Command {
+    /// List recently recorded timers.
+    History(HistoryArgs),
+    /// Show statistics over the recorded history, or export them.
+    Stats(StatsArgs),
+    /// Export the recorded history, like to an iCalendar file to be imported in any calendar.
+    Export(ExportArgs),
+    /// Manage the config file at `~/.config/tomate/config.toml`.
+    #[command(subcommand)]
+    Config(ConfigCommand),
+    /// Run the session in the background, controlled by `tomate ctl` through a Unix socket.
+    Daemon(DaemonArgs),
+    /// Pause, resume, skip, inspect or stop the session of a running daemon.
+    Ctl(CtlArgs),
+    /// Run the session in the terminal and share it over TCP with the ones joining it.
+    Host(TeamHostArgs),
+    /// Follow the session of a host, pausing, resuming or skipping it for everyone.
+    Join(JoinArgs),
+}
+
+fn main() -> ExitCode {
+    if let Some(lang) = i18n::lang_from_args(env::args()) {
+        i18n::set_lang(lang);
+    }
+
+    match run(CliArgs::parse()) {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(error) => error::report(&error),
+    }
+}
+
+fn run(mut args: CliArgs) -> Result<()> {
+    match args.command.take() {
+        Some(Command::History(history_args)) => {
+            load_config(args.lang)?;
+            history::show_history(history_args)
+        }
+        Some(Command::Stats(stats_args)) => {
+            load_config(args.lang)?;
+            stats::show_stats(stats_args)
+        }
+        Some(Command::Export(export_args)) => {
+            load_config(args.lang)?;
+            calendar::run_export(export_args)
+        }
+        Some(Command::Config(config_command)) => {
+            if let Some(lang) = args.lang {
+                i18n::set_lang(lang);
+            }
+            config::run_config_command(config_command)
+        }
+        Some(Command::Daemon(daemon_args)) => {
+            let config = load_config(args.lang)?;
+            run_daemon(daemon_args, config)
+        }
+        Some(Command::Ctl(ctl_args)) => {
+            load_config(args.lang)?;
+            control::run_ctl(ctl_args)
+        }
+        Some(Command::Host(host_args)) => {
+            let config = load_config(args.lang)?;
+
+            terminal::enable_raw_mode();
+            terminal::restore_on_ctrl_c();
+
+            let display = display(&host_args.session, args.tui);
+            let status = SharedStatus::default();
+            let (input, participants) = team::spawn_host(&host_args.team, status.clone(), spawn_stdin_channel())?;
+
+            build_tomato(host_args.session, config, input)?
+                .set_ask_task(args.ask_task)
+                .set_status(status)
+                .set_participants(participants)
+                .set_display(display)
+                .run_sprint();
+
+            terminal::restore();
+            Ok(())
+        }
+        Some(Command::Join(join_args)) => {
+            let config = load_config(args.lang)?;
+            let notifier = config.notifier()?;
+
+            terminal::enable_raw_mode();
+            terminal::restore_on_ctrl_c();
+
+            team::run_join(join_args, notifier)
+        }
+        None => {
+            let config = load_config(args.lang)?;
+
+            terminal::enable_raw_mode();
+            terminal::restore_on_ctrl_c();
+
+            let display = display(&args.session, args.tui);
+
+            build_tomato(args.session, config, spawn_stdin_channel())?
+                .set_ask_task(args.ask_task)
+                .set_display(display)
+                .run_sprint();
+
+            terminal::restore();
+            Ok(())
+        }
+    }
 }
 
-fn main() {
-    let args = CliArgs::parse();
+fn load_config(lang: Option<Lang>) -> Result<Config> {
+    let config = Config::load()?;
+
+    i18n::set_lang(lang.or(config.lang).unwrap_or_else(Lang::detect));
+    colors::set_palette(config.colors);
+    config.warn_unknown_keys();
+
+    Ok(config)
+}
+
+/// How the timer is drawn in the terminal, nothing when the state for the bars takes STDOUT.
+fn display(session: &SessionArgs, tui: bool) -> Display {
+    if session.status.takes_stdout() {
+        Display::Headless
+    } else if tui {
+        Display::Tui
+    } else {
+        Display::Line
+    }
+}
+
+fn run_daemon(args: DaemonArgs, config: Config) -> Result<()> {
+    let path = args.socket.path()?;
+    let status = SharedStatus::default();
+    let input = control::spawn_socket_channel(&path, status.clone())?;
+
+    showln!(render(t().daemon_listening, &[("path", &path.display())]).dimmed());
+
+    let result = build_tomato(args.session, config, input)
+        .map(|tomato| tomato.set_status(status).set_display(Display::Headless).run_sprint());
+
+    // Even if the session couldn't start, the socket isn't left behind
+    let _ = fs::remove_file(path);
+
+    result
+}
 
-    let mut tomato = Tomato::new();
+fn build_tomato(args: SessionArgs, mut config: Config, input: Receiver<Input>) -> Result<Tomato> {
+    let checkpoint = match args.resume {
+        Some(offline_time) => Some((Checkpoint::load()?, offline_time)),
+        None => None,
+    };
+
+    // The flags still apply when resuming, on top of the plan of the interrupted session
+    let mut plan = match &checkpoint {
+        Some((checkpoint, _)) => checkpoint.plan.clone(),
+        None => config.take_plan()?,
+    };
+
+    if let Some(path) = &args.plan {
+        plan = PlanFile::from_file(path)?.apply(plan)?;
+    }
 
     if let Some(work) = args.work_time {
-        tomato = tomato.set_work_time(work);
+        plan = plan.set_work_time(work.0)?;
     }
     if let Some(rest) = args.rest_time {
-        tomato = tomato.set_rest_time(rest);
+        plan = plan.set_rest_time(rest.0)?;
+    }
+    if let Some(cycles) = args.cycles {
+        plan = plan.set_cycles(cycles)?;
+    }
+    if let Some(every) = args.long_break_every {
+        plan = plan.set_long_break_every(every)?;
+    }
+    if let Some(long_rest) = args.long_rest_time {
+        plan = plan.set_long_rest_time(long_rest.0)?;
+    }
+    for cycle_override in args.cycle_overrides {
+        plan = plan.add_override(cycle_override);
+    }
+
+    let mut tomato = Tomato::new(plan.validate()?, input)
+        .set_notifier(Box::new(config.notifier()?))
+        .set_hooks(Hooks::new(config.hooks))
+        .set_messages(config.messages);
+
+    if !args.silent {
+        tomato = tomato.set_sounds(Sounds::new(config.sounds));
+    }
+
+    // Sped up, for real or with `--dry-run`, the timers aren't worth recording
+    match (args.speed, args.dry_run) {
+        (Some(factor), _) => tomato = tomato.set_clock(Rc::new(ScaledClock::new(factor))),
+        (None, true) => tomato = tomato.set_clock(Rc::new(SimulatedClock::default())),
+        (None, false) => {}
+    }
+
+    if args.overtime || config.overtime.enabled {
+        tomato = tomato.set_overtime(config.overtime.rest_ratio);
+    }
+
+    if args.adaptive_rest || config.adaptive_rest.enabled {
+        tomato = tomato.set_adaptive_rest(AdaptiveRest::new(&config.adaptive_rest));
+    }
+
+    let mut goals = config.goals;
+    goals.daily = args.daily_goal.or(goals.daily);
+    goals.weekly = args.weekly_goal.or(goals.weekly);
+    if let Some(goals) = Goals::load(goals)? {
+        tomato = tomato.set_goals(goals);
+    }
+
+    if let Some(picker) = ActivityPicker::new(config.break_activities) {
+        tomato = tomato.set_activity_picker(picker);
     }
 
-    tomato.run_sprint();
+    if let Some(detector) = IdleDetector::new(config.idle) {
+        tomato = tomato.set_idle_detector(detector);
+    }
+
+    if let Some(writer) = args.status.open()? {
+        tomato = tomato.set_status_writer(writer);
+    }
+
+    if let Some(emojis) = config.reward_emojis {
+        tomato = tomato.set_reward_emojis(emojis);
+    }
+    if let Some(emojis) = config.micro_management_emojis {
+        tomato = tomato.set_micro_management_emojis(emojis);
+    }
+
+    Ok(match checkpoint {
+        Some((checkpoint, offline_time)) => {
+            let task = args.task.or(checkpoint.task.clone());
+            tomato.set_task(task).set_resume(checkpoint, offline_time)
+        }
+        None => tomato.set_task(args.task),
+    })
 }
 
 struct Tomato {
-    work_time: u32,
-    rest_time: u32,
-    current_tomato: u64,
-    stdin_receiver: Receiver<String>,
-    reward_emoji_iter: Box<dyn Iterator<Item = &'static str>>,
-    micro_management_emoji_iter: Box<dyn Iterator<Item = &'static str>>,
-    bar_messager: BarMessager,
+    plan: SessionPlan,
+    current_cycle: Cycle,
+    task: Option<String>,
+    ask_task: bool,
+    input: Receiver<Input>,
+    clock: Rc<dyn Clock>,
+    /// Whether the timers go to the history and the checkpoint, not with a sped up clock.
+    recording: bool,
+    status: Option<SharedStatus>,
+    /// Everyone in the session, when hosting it with `tomate host`.
+    participants: Option<Participants>,
+    status_writer: Option<StatusWriter>,
+    display: Display,
+    stopped: bool,
+    stage_duration: Duration,
+    stage_started_at: DateTime<Local>,
+    /// Where to continue the session from, see [`Tomato::resume_from`].
+    resume: Option<(Checkpoint, OfflineTime)>,
+    /// Where to continue the stage from, taken by [`Tomato::start_stage`].
+    resume_point: Option<ResumePoint>,
+    start_paused: bool,
+    /// When the last checkpoint was saved, and whether the timer was paused then.
+    last_checkpoint: Option<(Instant, bool)>,
+    checkpoint_failed: bool,
+    completed_earlier_today: u32,
+    summary: SessionSummary,
+    notifier: Box<dyn Notifier>,
+    sounds: Option<Sounds>,
+    hooks: Hooks,
+    idle_detector: Option<IdleDetector>,
+    goals: Option<Goals>,
+    adaptive_rest: Option<AdaptiveRest>,
+    /// Why the next break has the length it has, shown when it starts.
+    rest_reasons: Option<String>,
+    activity_picker: Option<ActivityPicker>,
+    /// Suggested for the coming break, asked about at its end.
+    activity: Option<String>,
+    /// `Some` in overtime mode, with how much of the overtime is added to the next break.
+    overtime_rest_ratio: Option<f64>,
+    messages: Messages,
+    reward_emoji_iter: Box<dyn Iterator<Item = String>>,
+    micro_management_emoji_iter: Box<dyn Iterator<Item = String>>,
+    /// `None` without a bar server, or once sending to it failed.
+    bar_messager: Option<BarMessager>,
 }
 
 impl Tomato {
-    pub fn new() -> Self {
+    pub fn new(plan: SessionPlan, input: Receiver<Input>) -> Self {
         Self {
-            work_time: 25,
-            rest_time: 5,
-            current_tomato: 0,
-            stdin_receiver: spawn_stdin_channel(),
-            reward_emoji_iter: Box::new(["🍅", "🥗", "🍝", "🍕"].into_iter().cycle()),
-            micro_management_emoji_iter: Box::new(["👀", "🔫", "👮", "🚨"].into_iter().cycle()),
-            bar_messager: BarMessager::new().unwrap_or_explode("Failed to connect bar messager"),
+            current_cycle: plan.cycle(0),
+            plan,
+            task: None,
+            ask_task: false,
+            input,
+            clock: Rc::new(SystemClock),
+            recording: true,
+            status: None,
+            participants: None,
+            status_writer: None,
+            display: Display::Line,
+            stopped: false,
+            stage_duration: Duration::ZERO,
+            stage_started_at: Local::now(),
+            resume: None,
+            resume_point: None,
+            start_paused: false,
+            last_checkpoint: None,
+            checkpoint_failed: false,
+            completed_earlier_today: 0,
+            summary: SessionSummary::default(),
+            notifier: Box::new(Chain(vec![Box::new(Desktop), Box::new(Bell)])),
+            sounds: None,
+            hooks: Hooks::new(HookConfig::default()),
+            idle_detector: None,
+            goals: None,
+            adaptive_rest: None,
+            rest_reasons: None,
+            activity_picker: None,
+            activity: None,
+            overtime_rest_ratio: None,
+            messages: Messages::default(),
+            reward_emoji_iter: Box::new(["🍅", "🥗", "🍝", "🍕"].into_iter().map(String::from).cycle()),
+            micro_management_emoji_iter: Box::new(["👀", "🔫", "👮", "🚨"].into_iter().map(String::from).cycle()),
+            bar_messager: BarMessager::new()
+                .or_error(TomateError::Io, t().bar_connect_failed)
+                .or_warn(),
         }
     }
 
-    pub fn set_work_time(self, work_time: u32) -> Self {
-        (work_time != 0).unwrap_or_explode("the work_time argument can't be zero!");
+    pub fn set_notifier(self, notifier: Box<dyn Notifier>) -> Self {
+        Self { notifier, ..self }
+    }
 
-        (work_time < 60).unwrap_or_explode("the work_time argument cannot be bigger than a hour.");
+    pub fn set_hooks(self, hooks: Hooks) -> Self {
+        Self { hooks, ..self }
+    }
 
-        Self { work_time, ..self }
+    /// Pauses the tomatoes by itself when the user walks away.
+    pub fn set_idle_detector(self, detector: IdleDetector) -> Self {
+        Self {
+            idle_detector: Some(detector),
+            ..self
+        }
     }
 
-    pub fn set_rest_time(self, rest_time: u32) -> Self {
-        (rest_time != 0).unwrap_or_explode("the rest_time argument can't be zero!");
+    /// Counts up when a tomato ends instead of starting the break, until the user takes it.
+    pub fn set_overtime(self, rest_ratio: f64) -> Self {
+        Self {
+            overtime_rest_ratio: Some(rest_ratio),
+            ..self
+        }
+    }
 
-        (rest_time < 60).unwrap_or_explode("the rest_time argument cannot be bigger than a hour.");
+    pub fn set_sounds(self, sounds: Sounds) -> Self {
+        Self {
+            sounds: Some(sounds),
+            ..self
+        }
+    }
 
-        Self { rest_time, ..self }
+    pub fn set_messages(self, messages: Messages) -> Self {
+        Self { messages, ..self }
+    }
+
+    pub fn set_reward_emojis(self, emojis: Vec<String>) -> Self {
+        Self {
+            reward_emoji_iter: Box::new(emojis.into_iter().cycle()),
+            ..self
+        }
+    }
+
+    pub fn set_micro_management_emojis(self, emojis: Vec<String>) -> Self {
+        Self {
+            micro_management_emoji_iter: Box::new(emojis.into_iter().cycle()),
+            ..self
+        }
+    }
+
+    pub fn set_task(self, task: Option<String>) -> Self {
+        Self { task, ..self }
+    }
+
+    pub fn set_ask_task(self, ask_task: bool) -> Self {
+        Self { ask_task, ..self }
+    }
+
+    /// Continues the interrupted session instead of starting from the first tomato.
+    pub fn set_resume(self, checkpoint: Checkpoint, offline_time: OfflineTime) -> Self {
+        Self {
+            resume: Some((checkpoint, offline_time)),
+            ..self
+        }
+    }
+
+    /// Shares the state of the timer, updated at every tick.
+    pub fn set_status(self, status: SharedStatus) -> Self {
+        Self {
+            status: Some(status),
+            ..self
+        }
+    }
+
+    pub fn set_activity_picker(self, activity_picker: ActivityPicker) -> Self {
+        Self {
+            activity_picker: Some(activity_picker),
+            ..self
+        }
+    }
+
+    pub fn set_adaptive_rest(self, adaptive_rest: AdaptiveRest) -> Self {
+        Self {
+            adaptive_rest: Some(adaptive_rest),
+            ..self
+        }
+    }
+
+    /// Drives the timers with another clock, which isn't the real time, so nothing is recorded.
+    pub fn set_clock(self, clock: Rc<dyn Clock>) -> Self {
+        Self {
+            clock,
+            recording: false,
+            ..self
+        }
+    }
+
+    pub fn set_goals(self, goals: Goals) -> Self {
+        Self {
+            goals: Some(goals),
+            ..self
+        }
+    }
+
+    pub fn set_participants(self, participants: Participants) -> Self {
+        Self {
+            participants: Some(participants),
+            ..self
+        }
+    }
+
+    /// Writes the state of the timer at every tick, for status bars.
+    pub fn set_status_writer(self, writer: StatusWriter) -> Self {
+        Self {
+            status_writer: Some(writer),
+            ..self
+        }
+    }
+
+    pub fn set_display(self, display: Display) -> Self {
+        let completed_earlier_today = match display {
+            Display::Tui => history::completed_on(Local::now().date_naive())
+                .or_warn()
+                .unwrap_or_default(),
+            _ => 0,
+        };
+
+        Self {
+            display,
+            completed_earlier_today,
+            ..self
+        }
     }
 
     pub fn run_sprint(mut self) {
-        while self.current_tomato < 4 {
+        let resumed_stage = self
+            .resume
+            .take()
+            .map(|(checkpoint, offline_time)| self.resume_from(checkpoint, offline_time));
+
+        if !self.recording {
+            showln!(t().not_recording.dimmed());
+        }
+
+        if let Display::Tui = self.display {
+            terminal::enter_alternate_screen();
+        }
+
+        if let Some(stage) = resumed_stage {
+            self.run_cycle_from(stage);
+        }
+
+        while self.current_cycle.number < self.plan.cycles && !self.stopped {
             self.run_once();
         }
 
-        self.bar_messager.send_message(BarMessage::Disconnecting).unwrap();
+        // The summary has to outlive the full screen
+        terminal::leave_alternate_screen();
+
+        if self.stopped {
+            self.show_summary();
+        }
+
+        if let Some(writer) = &mut self.status_writer {
+            writer.finish();
+        }
+
+        // Done or stopped on purpose, either way there's nothing left to resume
+        if self.recording {
+            Checkpoint::remove();
+        }
+
+        self.run_hook(Event::SprintEnd, None, Duration::ZERO);
+        self.hooks.wait();
+
+        send_to_bar(&mut self.bar_messager, BarMessage::Disconnecting);
+    }
+
+    fn show_summary(&self) {
+        let SessionSummary {
+            completed,
+            skipped,
+            focused,
+            paused,
+        } = self.summary;
+
+        showln!(
+            "\n  ",
+            render(
+                t().session_summary,
+                &[
+                    ("completed", &completed.green()),
+                    ("skipped", &skipped.red()),
+                    ("focused", &Time::from(focused).color(palette().time.0)),
+                    ("paused", &Time::from(paused).color(palette().time.0)),
+                ],
+            ),
+        );
     }
 
     fn run_once(&mut self) {
-        self.current_tomato += 1;
+        self.current_cycle = self.plan.cycle(self.current_cycle.number + 1);
+
+        self.run_cycle_from(Stage::Work);
+    }
+
+    fn run_cycle_from(&mut self, stage: Stage) {
+        if let Stage::Work = stage {
+            self.run_work_timer();
+        }
+
+        if !self.stopped {
+            self.run_rest_timer();
+        }
+    }
+
+    /// Moves to the cycle of the checkpoint, asking whether the time offline counts if needed,
+    /// and returns the stage to continue from.
+    fn resume_from(&mut self, checkpoint: Checkpoint, offline_time: OfflineTime) -> Stage {
+        let offline = checkpoint.offline();
+        let mut remaining = Duration::from_secs(checkpoint.remaining_secs);
+        let mut overtime = checkpoint.overtime_secs.map(Duration::from_secs);
+
+        // A paused timer wouldn't have moved anyway, nor one that hadn't started
+        let count_offline = !checkpoint.paused
+            && !checkpoint.upcoming
+            && match offline_time {
+                OfflineTime::Count => true,
+                OfflineTime::Discard => false,
+                OfflineTime::Ask => {
+                    !matches!(self.display, Display::Headless)
+                        && io::stdin().is_terminal()
+                        && self.ask_count_offline(offline)
+                }
+            };
+
+        if count_offline {
+            remaining = remaining.saturating_sub(offline);
+            overtime = overtime.map(|overtime| overtime + offline);
+        }
+
+        self.current_cycle = self.plan.cycle(checkpoint.tomato);
+
+        if checkpoint.upcoming {
+            // Started from the beginning, with the break lengthened or shortened by the last tomato
+            if let Stage::Rest = checkpoint.stage {
+                self.current_cycle.rest_time = Duration::from_secs(checkpoint.stage_secs);
+            }
+        } else {
+            self.resume_point = Some(ResumePoint {
+                remaining,
+                stage_duration: Duration::from_secs(checkpoint.stage_secs),
+                started_at: checkpoint.started_at,
+                paused: checkpoint.paused,
+                overtime,
+            });
+        }
+
+        let tomato = format!("{}/{}", checkpoint.tomato, self.plan.cycles);
+        let (message, time) = match overtime {
+            Some(overtime) => (t().resuming_overtime, overtime),
+            None => (t().resuming, remaining),
+        };
+        showln!(render(
+            message,
+            &[
+                ("stage", &checkpoint.stage),
+                ("tomato", &tomato.color(palette().tomato.0)),
+                ("time", &Time::from(time).color(palette().time.0)),
+            ],
+        ));
+
+        checkpoint.stage
+    }
+
+    fn ask_count_offline(&mut self, offline: Duration) -> bool {
+        self.ask_yes_or_no(&render(t().ask_count_offline, &[("time", &describe(offline))]))
+    }
+
+    /// Asks for a single key, anything but yes is no.
+    fn ask_yes_or_no(&mut self, question: &str) -> bool {
+        show!(question);
+
+        let answer = loop {
+            if let Input::Key(key) = self.input.recv().unwrap() {
+                break key;
+            }
+        };
+        showln!(answer as char);
+
+        matches!(answer, b'y' | b'Y' | b's' | b'S')
+    }
+
+    /// Resets the stats and the duration of the stage, or picks them up from the checkpoint when resuming,
+    /// returning how long the timer has to run.
+    fn start_stage(&mut self, total_duration: Duration, stats: &mut TimerStats) -> Duration {
+        *stats = TimerStats::default();
+        self.stage_duration = total_duration;
+        self.stage_started_at = Local::now();
+
+        let Some(resumed) = self.resume_point.take() else {
+            return total_duration;
+        };
+
+        self.stage_duration = resumed.stage_duration;
+        self.stage_started_at = resumed.started_at;
+        self.start_paused = resumed.paused;
+        stats.elapsed = resumed.stage_duration.saturating_sub(resumed.remaining);
+
+        resumed.remaining
+    }
+
+    fn progress(&self) -> Progress {
+        self.plan.progress(&self.current_cycle)
+    }
+
+    /// Notifies through the configured backends, only warning if all of them fail.
+    fn notify(&self, message: String) {
+        if self.notifier.notify(&message).is_err() {
+            warn(&TomateError::Io(render(t().notification_failed, &[("body", &message)])));
+        }
+    }
+
+    fn run_hook(&mut self, event: Event, stage: Option<Stage>, remaining: Duration) {
+        self.hooks.run(&hooks::Context {
+            event,
+            stage,
+            tomato: self.current_cycle.number,
+            tomatoes: self.plan.cycles,
+            remaining,
+            task: self.task.as_deref(),
+        });
+    }
+
+    fn play(&self, cue: Cue) {
+        if let Some(sounds) = &self.sounds {
+            sounds.play(cue);
+        }
+    }
+
+    /// The progress towards the goals to be appended to messages, if there are any.
+    fn goal_suffix(&self) -> String {
+        self.goals
+            .as_ref()
+            .map(|goals| format!(" · {}", goals.progress()))
+            .unwrap_or_default()
+    }
+
+    /// Fills a template of the `[messages]` table, all of them get the same placeholders. `duration` is
+    /// the one of the tomato, or of the coming break once it's done.
+    fn render_message(&self, template: &str, duration: Duration, emoji: &str) -> String {
+        let progress = self.progress();
 
-        self.run_work_timer();
-        self.run_rest_timer();
+        render(
+            template,
+            &[
+                ("tomato", &self.current_cycle.number),
+                ("cycles", &progress.cycles),
+                ("duration", &describe(duration)),
+                ("minutes", &(duration.as_secs() / 60)),
+                ("task", &self.task_suffix()),
+                ("progress", &progress),
+                ("emoji", &emoji),
+            ],
+        )
+    }
+
+    /// Formats the task label to be appended to messages, if there's one.
+    fn task_suffix(&self) -> String {
+        self.task.as_ref().map(|task| format!(": {task}")).unwrap_or_default()
+    }
+
+    /// Everyone in the team session, `None` when not hosting one.
+    fn participants(&self) -> Option<String> {
+        let participants = self.participants.as_ref()?.lock().unwrap();
+
+        Some(format!("👥 {}", participants.join(", ")))
+    }
+
+    fn ask_for_task(&mut self) {
+        let prompt = match &self.task {
+            Some(task) => render(t().ask_task_keep, &[("task", task)]),
+            None => t().ask_task_none.to_owned(),
+        };
+        if let Display::Tui = self.display {
+            tui::clear_for_prompt();
+        }
+        show!(prompt);
+
+        // Typing the task needs echo and line editing
+        terminal::disable_raw_mode();
+
+        let mut line = Vec::new();
+        loop {
+            match self.input.recv().unwrap() {
+                Input::Key(b'\n') => break,
+                Input::Key(key) => line.push(key),
+                Input::Control(_) | Input::Resize => {}
+            }
+        }
+
+        terminal::enable_raw_mode();
+
+        let line = String::from_utf8_lossy(&line);
+        let line = line.trim();
+
+        if !line.is_empty() {
+            self.task = Some(line.to_owned());
+        }
     }
 
     fn run_work_timer(&mut self) {
-        send_notification(format!("Iniciando tomate de {} minutos!", self.work_time));
-        showln!(
-            format_args!("[{}]", self.current_tomato).red(),
-            " Tomate de ",
-            format_args!("{} minutos", self.work_time).blue(),
-            " iniciado! ",
-        );
+        // Already started before the interruption
+        let resuming = self.resume_point.is_some();
+        let resumed_overtime = self.resume_point.as_ref().and_then(|resumed| resumed.overtime);
 
-        let total_duration = MINUTE * self.work_time;
-        let half_duration = total_duration / 2;
+        if self.ask_task && !resuming {
+            self.ask_for_task();
+        }
 
-        let was_skipped = self.run_pausable_timer(half_duration, half_duration, Stage::Work);
+        let Cycle { number, work_time, .. } = self.current_cycle;
+        let progress = self.progress();
+        let task_suffix = self.task_suffix();
 
-        // Extra logic to be able to send a notification at the half
-        if !was_skipped {
-            send_notification(format!(
-                "Na metade! Você está focado, não está? {}",
-                self.micro_management_emoji_iter.next().unwrap(),
-            ));
-            self.run_pausable_timer(half_duration, None, Stage::Work);
+        if !resuming {
+            self.play(Cue::WorkStart);
+            self.run_hook(Event::WorkStart, Some(Stage::Work), work_time);
+            let emoji = self.micro_management_emoji_iter.next().unwrap();
+            self.notify(self.render_message(self.messages.work_start(), work_time, &emoji));
+            let duration = describe(work_time);
+            showln!(
+                format_args!("[{number}/{}] ", progress.cycles).color(palette().tomato.0),
+                render(
+                    t().work_started,
+                    &[
+                        ("duration", &duration.color(palette().time.0)),
+                        ("task", &task_suffix.color(palette().task.0)),
+                    ],
+                ),
+                format_args!(" ({progress}{})", self.goal_suffix()).dimmed(),
+            );
+        }
+
+        let total_duration = work_time;
+        // Whole seconds, since the timer ticks by one second
+        let first_half = Duration::from_secs(total_duration.as_secs() / 2);
+        let second_half = total_duration - first_half;
+
+        let mut stats = TimerStats::default();
+        let mut remaining = self.start_stage(total_duration, &mut stats);
+
+        let end = loop {
+            let mut end = TimerEnd::Finished;
+
+            // Extra logic to be able to send a notification at the half, unless resuming past it
+            if remaining > second_half {
+                end = self.run_pausable_timer(remaining - second_half, second_half, Stage::Work, &mut stats);
+
+                if end == TimerEnd::Finished {
+                    let emoji = self.micro_management_emoji_iter.next().unwrap();
+                    self.play(Cue::Halfway);
+                    self.run_hook(Event::Halfway, Some(Stage::Work), second_half);
+                    self.notify(self.render_message(self.messages.halfway(), total_duration, &emoji));
+                }
+            }
+
+            if end == TimerEnd::Finished {
+                end = self.run_pausable_timer(remaining.min(second_half), None, Stage::Work, &mut stats);
+            }
+
+            if end != TimerEnd::Restarted {
+                break end;
+            }
+
+            remaining = self.start_stage(total_duration, &mut stats);
+        };
+
+        match (end, self.overtime_rest_ratio) {
+            (TimerEnd::Finished, Some(rest_ratio)) => {
+                self.run_overtime(rest_ratio, resumed_overtime.unwrap_or_default(), &mut stats)
+            }
+            // Resumed without the overtime mode, what was counted before still counts
+            _ => stats.overtime = resumed_overtime.unwrap_or_default(),
+        }
+
+        self.summary.add(Stage::Work, &stats);
+        self.adapt_rest(&stats);
+
+        self.record(&Record::new(
+            Stage::Work,
+            number,
+            self.task.clone(),
+            self.stage_started_at,
+            total_duration,
+            &stats,
+        ));
+        self.save_upcoming_checkpoint(Stage::Rest);
+
+        self.run_hook(
+            Event::WorkEnd,
+            Some(Stage::Work),
+            total_duration.saturating_sub(stats.elapsed),
+        );
+
+        // The history is read again, but not being able to doesn't stop the sprint
+        let reached = self
+            .goals
+            .as_mut()
+            .and_then(|goals| goals.refresh().or_warn())
+            .unwrap_or_default();
+
+        if self.stopped {
+            return;
         }
 
         let reward_emoji = self.reward_emoji_iter.next().unwrap();
 
+        let tomato = format!("{number}/{}", progress.cycles);
+        let overtime = if stats.overtime.is_zero() {
+            String::new()
+        } else {
+            format!(
+                " ({})",
+                render(t().overtime_done, &[("time", &Time::from(stats.overtime))])
+            )
+        };
         showln!(
             "\n  ",
-            "[Eba!]".green(),
-            " Tomate ",
-            format_args!("{}", self.current_tomato).red(),
-            " concluído!".green(),
-            " Sua recompensa: ",
-            reward_emoji,
+            t().hooray.green(),
+            " ",
+            render(
+                t().tomato_done,
+                &[("tomato", &tomato.color(palette().tomato.0)), ("emoji", &reward_emoji)],
+            ),
+            overtime.yellow(),
+            self.goal_suffix().dimmed(),
         );
 
-        send_notification(format!(
-            "Tomate {} concluído! {reward_emoji} Descanse {} minutos.",
-            self.current_tomato, self.rest_time,
-        ));
+        let Cycle {
+            rest_time, long_break, ..
+        } = self.current_cycle;
+        let template = if long_break {
+            self.messages.long_work_done()
+        } else {
+            self.messages.work_done()
+        };
+
+        let mut message = self.render_message(template, rest_time, &reward_emoji);
+
+        self.activity = self.activity_picker.as_mut().map(ActivityPicker::pick);
+        if let Some(activity) = &self.activity {
+            message += "\n";
+            message += &render(t().activity_suggestion, &[("activity", activity)]);
+        }
+
+        self.notify(message);
+
+        self.celebrate_goals(reached);
+    }
+
+    /// Appends to the history, unless the clock isn't the real one.
+    fn record(&self, record: &Record) {
+        if self.recording {
+            history::append(record).or_warn();
+        }
+    }
+
+    /// Picks the length of the coming break in adaptive mode, unless it's a long one.
+    fn adapt_rest(&mut self, stats: &TimerStats) {
+        let Some(adaptive_rest) = &mut self.adaptive_rest else {
+            return;
+        };
+
+        adaptive_rest.record(stats);
+
+        if self.current_cycle.long_break {
+            return;
+        }
+
+        let planned = self.plan.cycle(self.current_cycle.number).rest_time;
+        let overtime_extra = self.current_cycle.rest_time.saturating_sub(planned);
+        let (rest_time, reasons) = adaptive_rest.adjust(planned, overtime_extra);
+
+        self.current_cycle.rest_time = rest_time;
+        self.rest_reasons = Some(reasons);
+    }
+
+    /// Celebrates the goals just reached, in the terminal and with a notification of their own.
+    fn celebrate_goals(&self, reached: Vec<Period>) {
+        let Some(goals) = &self.goals else {
+            return;
+        };
+
+        for period in reached {
+            let message = goals.reached_message(period);
+            showln!("  ", message.green());
+            self.notify(message);
+        }
+    }
+
+    /// Counts up after the tomato ended, until the user takes the break, which is lengthened
+    /// by the given ratio of the overtime. Continues from the overtime `already` counted when resuming.
+    fn run_overtime(&mut self, rest_ratio: f64, already: Duration, stats: &mut TimerStats) {
+        if already.is_zero() {
+            let emoji = self.micro_management_emoji_iter.next().unwrap();
+            self.notify(self.render_message(self.messages.overtime(), self.current_cycle.work_time, &emoji));
+        }
+
+        let clock = self.clock.clone();
+        let started_at = clock.now();
+        let counted = || clock.now().saturating_duration_since(started_at);
+        // Saved at the first tick, like the timers
+        self.last_checkpoint = None;
+
+        loop {
+            let counted_so_far = counted();
+            let elapsed = already + counted_so_far;
+            self.draw_overtime(elapsed);
+            self.update_status(Stage::Work, Duration::ZERO, false, elapsed);
+            self.save_overtime_checkpoint(elapsed);
+
+            // A simulated clock would count up as fast as it can, only a key or command ends it
+            let input = if clock.is_simulated() {
+                self.input.recv().unwrap()
+            } else {
+                let next_tick = started_at + Duration::from_secs(counted_so_far.as_secs() + 1);
+                let Ok(input) = recv_deadline(&self.input, clock.wait_until(next_tick)) else {
+                    continue;
+                };
+                input
+            };
+
+            match input.control() {
+                Some(Control::Pause | Control::TogglePause | Control::Skip) => break,
+                Some(Control::Stop) => {
+                    self.stopped = true;
+                    break;
+                }
+                _ => {}
+            }
+        }
+
+        stats.overtime = already + counted();
+
+        if let Display::Line = self.display {
+            clear_footer();
+        }
+
+        let extra = Duration::from_secs((stats.overtime.as_secs_f64() * rest_ratio).round() as u64);
+        self.current_cycle.rest_time += extra;
     }
 
     fn run_rest_timer(&mut self) {
-        let total_duration = MINUTE * self.rest_time;
-        self.run_pausable_timer(total_duration, None, Stage::Rest);
+        let total_duration = self.current_cycle.rest_time;
+
+        if self.resume_point.is_none() {
+            if let Some(activity) = &self.activity {
+                showln!(
+                    "  ",
+                    render(
+                        t().activity_suggestion,
+                        &[("activity", &activity.color(palette().task.0))]
+                    ),
+                );
+            }
+            if let Some(reasons) = self.rest_reasons.take() {
+                showln!(
+                    "  ",
+                    render(
+                        t().adaptive_rest,
+                        &[
+                            ("duration", &describe(total_duration).color(palette().time.0)),
+                            ("reasons", &reasons.dimmed()),
+                        ],
+                    ),
+                );
+            }
+            self.run_hook(Event::RestStart, Some(Stage::Rest), total_duration);
+        }
+
+        let mut stats = TimerStats::default();
+        let mut remaining = self.start_stage(total_duration, &mut stats);
+
+        while self.run_pausable_timer(remaining, None, Stage::Rest, &mut stats) == TimerEnd::Restarted {
+            remaining = self.start_stage(total_duration, &mut stats);
+        }
         println!();
+
+        if !self.stopped {
+            self.play(Cue::RestEnd);
+        }
+
+        self.summary.add(Stage::Rest, &stats);
+
+        let mut record = Record::new(
+            Stage::Rest,
+            self.current_cycle.number,
+            self.task.clone(),
+            self.stage_started_at,
+            total_duration,
+            &stats,
+        );
+
+        if let Some(activity) = self.activity.take() {
+            let ask = !self.stopped && self.activity_picker.as_ref().is_some_and(|picker| picker.ask);
+            if ask {
+                record.activity_done = self.ask_over_timer(&render(t().ask_activity_done, &[("activity", &activity)]));
+            }
+            record.activity = Some(activity);
+        }
+
+        self.record(&record);
+        self.save_upcoming_checkpoint(Stage::Work);
+
+        self.run_hook(
+            Event::RestEnd,
+            Some(Stage::Rest),
+            total_duration.saturating_sub(stats.elapsed),
+        );
     }
 
     fn run_pausable_timer(
         &mut self,
-        mut remaining: Duration,
+        duration: Duration,
         additional_time_to_display: impl Into<Option<Duration>>,
         status: Stage,
-    ) -> bool {
+        stats: &mut TimerStats,
+    ) -> TimerEnd {
         let additional_time_to_display = additional_time_to_display.into().unwrap_or_default();
-        let start_instant = Instant::now();
 
-        let increment = Duration::from_secs(1);
-        let mut increment_sum = increment;
+        let mut timer = if std::mem::take(&mut self.start_paused) {
+            Timer::start_paused(self.clock.clone(), duration)
+        } else {
+            Timer::start(self.clock.clone(), duration)
+        };
+        let events = timer.subscribe();
+        // Saved at the first tick of each timer
+        self.last_checkpoint = None;
+        // How long the user was idle when the timer paused by itself
+        let mut idle_pause = None;
+
+        let end = loop {
+            if timer.poll() == TimerState::Finished {
+                break TimerEnd::Finished;
+            }
+
+            // Only the tomatoes, being away is the point of the breaks
+            if let (Stage::Work, false) = (status, timer.is_paused()) {
+                if let Some(idle) = self.idle_detector.as_mut().and_then(IdleDetector::check) {
+                    timer.pause();
+                    idle_pause = Some(idle);
+                    self.show_idle_pause(status, idle);
+                    self.run_timer_hooks(&events, status, timer.remaining_secs() + additional_time_to_display);
+                }
+            }
+
+            let remaining = timer.remaining_secs() + additional_time_to_display;
+            let paused = timer.is_paused();
+
+            self.draw_timer(remaining, status, paused);
+            self.update_status(status, remaining, paused, Duration::ZERO);
+            self.save_checkpoint(status, remaining, paused);
+
+            // Sleep, unless paused, then only a key or command wakes it up
+            let input = if paused {
+                self.input.recv().unwrap()
+            } else if let Ok(input) = recv_deadline(&self.input, self.clock.wait_until(timer.next_tick())) {
+                input
+            } else {
+                continue;
+            };
 
-        let mut stdout = io::stdout();
+            let Some(control) = input.control() else {
+                continue;
+            };
 
-        while remaining != Duration::ZERO {
-            let time = Time::from(remaining + additional_time_to_display);
+            match control {
+                Control::Pause => timer.pause(),
+                Control::Resume => timer.resume(),
+                Control::TogglePause => timer.toggle_pause(),
+                Control::AddMinute => {
+                    timer.extend(MINUTE);
+                    self.stage_duration += MINUTE;
+                }
+                Control::RemoveMinute => {
+                    self.stage_duration -= timer.remaining().min(MINUTE);
+                    timer.shorten(MINUTE);
+                }
+                Control::Skip => timer.skip(),
+                Control::Restart => break TimerEnd::Restarted,
+                Control::Stop => {
+                    self.stopped = true;
+                    break TimerEnd::Skipped;
+                }
+            }
 
-            // Print line
-            write!(stdout, "{CLEAR_LINE}\r  {status} {time}          ").unwrap();
-            stdout.flush().unwrap();
-            self.bar_messager
-                .send_message(BarMessage::Running(time, status))
-                .unwrap();
+            self.run_timer_hooks(&events, status, timer.remaining_secs() + additional_time_to_display);
 
-            // Sleep
-            let instant_to_reach = start_instant + increment_sum;
+            if let (false, Some(idle)) = (timer.is_paused(), idle_pause) {
+                idle_pause = None;
 
-            if let Ok(line) = recv_deadline(&self.stdin_receiver, instant_to_reach) {
-                go_back_one_line();
+                let idle = idle.min(timer.elapsed());
+                if self.should_discard_idle(idle) {
+                    timer.rewind(idle);
+                    stats.idle += idle;
+                }
+                if let Some(detector) = &mut self.idle_detector {
+                    detector.postpone();
+                }
+            }
 
-                if !line.contains('p') {
-                    write!(stdout, "{CLEAR_LINE}\r  {status} {time} {} ", "(Paused)".red()).unwrap();
-                    stdout.flush().unwrap();
-                    self.bar_messager
-                        .send_message(BarMessage::Paused(time, status))
-                        .unwrap();
+            if timer.state() == TimerState::Skipped {
+                break TimerEnd::Skipped;
+            }
+        };
+
+        stats.elapsed += timer.elapsed();
+        stats.paused_for += timer.paused_for();
+        stats.pauses += timer.pauses();
+
+        if let Display::Line = self.display {
+            clear_footer();
+        }
+
+        if end == TimerEnd::Skipped {
+            let time = Time::from(timer.remaining_secs() + additional_time_to_display);
+            let skipped = render(t().skipped_at, &[("skipped", &t().skipped.red()), ("time", &time)]);
 
-                    if !self.wait_unpause() {
-                        return self.run_pausable_timer(remaining, additional_time_to_display, status);
-                    }
+            match self.display {
+                Display::Line => print!("{CLEAR_LINE}\r  {status} {skipped}"),
+                Display::Headless => {
+                    showln!(status, " ", skipped);
                 }
+                // Replaced by the next stage anyway
+                Display::Tui => {}
+            }
+            stats.skipped = true;
+        }
+
+        end
+    }
+
+    /// Runs the hooks of what happened to the timer since the last call.
+    fn run_timer_hooks(&mut self, events: &Receiver<TimerEvent>, status: Stage, remaining: Duration) {
+        for event in events.try_iter() {
+            let event = match event {
+                TimerEvent::Paused => Event::Pause,
+                TimerEvent::Resumed => Event::Resume,
+                TimerEvent::Skipped => Event::Skip,
+                _ => continue,
+            };
+            self.run_hook(event, Some(status), remaining);
+        }
+    }
+
+    fn show_idle_pause(&self, status: Stage, idle: Duration) {
+        let message = render(t().idle_paused, &[("idle", &describe(idle))]);
+
+        match self.display {
+            // Left above the timer line, which keeps being redrawn
+            Display::Line => {
+                clear_footer();
+                println!("{CLEAR_LINE}\r  {status} {}", message.yellow());
+            }
+            Display::Headless => {
+                showln!(status, " ", message);
+            }
+            // The paused label is already there
+            Display::Tui => {}
+        }
+    }
+
+    /// Whether the idle time before an automatic pause should be taken off the tomato.
+    fn should_discard_idle(&mut self, idle: Duration) -> bool {
+        let discard = self
+            .idle_detector
+            .as_ref()
+            .map_or(DiscardIdle::Never, |detector| detector.discard);
+
+        match discard {
+            DiscardIdle::Always => true,
+            DiscardIdle::Never => false,
+            DiscardIdle::Ask => self
+                .ask_over_timer(&render(t().ask_discard_idle, &[("idle", &describe(idle))]))
+                .unwrap_or(true),
+        }
+    }
+
+    /// Asks a yes or no question in place of the timer, `None` if there's no terminal to ask on.
+    fn ask_over_timer(&mut self, question: &str) -> Option<bool> {
+        if matches!(self.display, Display::Headless) || !io::stdin().is_terminal() {
+            return None;
+        }
+
+        match self.display {
+            Display::Line => {
+                clear_footer();
+                print!("{CLEAR_LINE}\r  ");
+            }
+            Display::Tui => tui::clear_for_prompt(),
+            Display::Headless => {}
+        }
+
+        Some(self.ask_yes_or_no(question))
+    }
+
+    /// Redraws the timer, either the line with the help footer below it or the full screen, and updates the bar.
+    fn draw_timer(&mut self, remaining: Duration, status: Stage, paused: bool) {
+        let time = Time::from(remaining);
+        let progress = self.progress();
+        let participants = self.participants();
+        let goal = self.goals.as_ref().map(Goals::progress);
+
+        if let Display::Tui = self.display {
+            tui::draw(&tui::Frame {
+                stage: status,
+                time,
+                paused,
+                overtime: false,
+                done: 1.0 - remaining.as_secs_f64() / self.stage_duration.as_secs_f64(),
+                progress,
+                task: self.task.as_deref(),
+                participants: participants.as_deref(),
+                goal: goal.as_deref(),
+                completed_today: self.completed_earlier_today + self.summary.completed,
+            });
+        }
+
+        if let Display::Line = self.display {
+            let mut line = if paused {
+                format!("{status} {time} {}", t().paused.red())
+            } else {
+                format!(
+                    "{status} {time}  {}{}",
+                    progress.dimmed(),
+                    self.task_suffix().color(palette().task.0),
+                )
+            };
+            if let Some(participants) = &participants {
+                line += &format!("  {}", participants.dimmed());
+            }
+
+            let mut stdout = io::stdout();
+            let _ = write!(
+                stdout,
+                "{CLEAR_LINE}\r  {line}\n{CLEAR_LINE}  {}\x1B[1A\r",
+                t().keys_help.dimmed()
+            );
+            let _ = stdout.flush();
+        }
+
+        let message = if paused {
+            BarMessage::Paused(time, status, progress, self.task.as_deref(), goal.as_deref())
+        } else {
+            BarMessage::Running(time, status, progress, self.task.as_deref(), goal.as_deref())
+        };
+        send_to_bar(&mut self.bar_messager, message);
+    }
+
+    /// Like [`Tomato::draw_timer`], with the time counted up after the tomato ended.
+    fn draw_overtime(&mut self, overtime: Duration) {
+        let time = Time::from(overtime);
+        let progress = self.progress();
+        let participants = self.participants();
+        let goal = self.goals.as_ref().map(Goals::progress);
+
+        if let Display::Tui = self.display {
+            tui::draw(&tui::Frame {
+                stage: Stage::Work,
+                time,
+                paused: false,
+                overtime: true,
+                done: 1.0,
+                progress,
+                task: self.task.as_deref(),
+                participants: participants.as_deref(),
+                goal: goal.as_deref(),
+                completed_today: self.completed_earlier_today + self.summary.completed,
+            });
+        }
+
+        if let Display::Line = self.display {
+            let mut stdout = io::stdout();
+            let _ = write!(
+                stdout,
+                "{CLEAR_LINE}\r  {} {} {}  {}{}  {}\n{CLEAR_LINE}  {}\x1B[1A\r",
+                Stage::Work,
+                format_args!("+{time}").yellow(),
+                t().overtime.yellow(),
+                progress.dimmed(),
+                self.task_suffix().color(palette().task.0),
+                participants.unwrap_or_default().dimmed(),
+                t().overtime_help.dimmed(),
+            );
+            let _ = stdout.flush();
+        }
+
+        let message = BarMessage::Overtime(time, Stage::Work, progress, self.task.as_deref(), goal.as_deref());
+        send_to_bar(&mut self.bar_messager, message);
+    }
+
+    /// Saves where the timer is at, every [`checkpoint::INTERVAL`] or when pausing and resuming.
+    fn save_checkpoint(&mut self, stage: Stage, remaining: Duration, paused: bool) {
+        if self.checkpoint_due(paused) {
+            self.write_checkpoint(self.checkpoint_at(stage, remaining, paused));
+        }
+    }
+
+    /// Saves the overtime counted so far, as often as the timer.
+    fn save_overtime_checkpoint(&mut self, overtime: Duration) {
+        if self.checkpoint_due(false) {
+            self.write_checkpoint(Checkpoint {
+                overtime_secs: Some(overtime.as_secs()),
+                ..self.checkpoint_at(Stage::Work, Duration::ZERO, false)
+            });
+        }
+    }
 
-                write!(stdout, "{CLEAR_LINE}\r  {status} {} at {time}.", "skipped".red()).unwrap();
-                return true;
+    /// Points the checkpoint at the stage coming next, once the current one is in the history, so resuming
+    /// doesn't record it again. There's nothing to resume after the last rest.
+    fn save_upcoming_checkpoint(&mut self, stage: Stage) {
+        let cycle = match stage {
+            Stage::Work => self.plan.cycle(self.current_cycle.number + 1),
+            Stage::Rest => self.current_cycle,
+        };
+
+        if cycle.number > self.plan.cycles {
+            if self.recording {
+                Checkpoint::remove();
             }
+            return;
+        }
+
+        let duration = match stage {
+            Stage::Work => cycle.work_time,
+            Stage::Rest => cycle.rest_time,
+        };
+
+        self.write_checkpoint(Checkpoint {
+            tomato: cycle.number,
+            started_at: Local::now(),
+            stage_secs: duration.as_secs(),
+            upcoming: true,
+            ..self.checkpoint_at(stage, duration, false)
+        });
+    }
+
+    /// Whether it's time for another checkpoint, every [`checkpoint::INTERVAL`] or when the pause changes.
+    fn checkpoint_due(&mut self, paused: bool) -> bool {
+        let due = self
+            .last_checkpoint
+            .is_none_or(|(saved_at, was_paused)| saved_at.elapsed() >= checkpoint::INTERVAL || was_paused != paused);
 
-            // Account for slept duration
-            increment_sum += increment;
-            remaining -= increment;
+        if due {
+            self.last_checkpoint = Some((Instant::now(), paused));
+        }
+
+        due
+    }
+
+    fn checkpoint_at(&self, stage: Stage, remaining: Duration, paused: bool) -> Checkpoint {
+        Checkpoint {
+            plan: self.plan.clone(),
+            tomato: self.current_cycle.number,
+            stage,
+            started_at: self.stage_started_at,
+            stage_secs: self.stage_duration.as_secs(),
+            remaining_secs: remaining.as_secs(),
+            paused,
+            overtime_secs: None,
+            upcoming: false,
+            task: self.task.clone(),
+            saved_at: Local::now(),
         }
-        false
     }
 
-    /// Returns if skipping was requested
-    fn wait_unpause(&self) -> bool {
-        let was_skipped = self.stdin_receiver.recv().unwrap().contains('p');
-        go_back_one_line();
-        was_skipped
+    /// Stops trying after the first failure, warning once is enough.
+    fn write_checkpoint(&mut self, checkpoint: Checkpoint) {
+        if self.checkpoint_failed || !self.recording {
+            return;
+        }
+
+        if let Err(error) = checkpoint.save() {
+            self.checkpoint_failed = true;
+            warn(&error);
+        }
+    }
+
+    /// Shares the state with the control socket and writes it for the status bars, if enabled.
+    fn update_status(&mut self, stage: Stage, remaining: Duration, paused: bool, overtime: Duration) {
+        if self.status.is_none() && self.status_writer.is_none() {
+            return;
+        }
+
+        let snapshot = Status {
+            stage: Some(stage),
+            remaining_secs: remaining.as_secs(),
+            paused,
+            overtime_secs: overtime.as_secs(),
+            cycle: self.current_cycle.number,
+            cycles: self.plan.cycles,
+            task: self.task.clone(),
+            goal: self.goals.as_ref().map(Goals::progress),
+        };
+
+        if let Some(writer) = &mut self.status_writer {
+            writer.write(
+                &snapshot,
+                1.0 - remaining.as_secs_f64() / self.stage_duration.as_secs_f64(),
+            );
+        }
+        if let Some(status) = &self.status {
+            *status.lock().unwrap() = snapshot;
+        }
     }
 }
 
-fn go_back_one_line() {
-    print!("\x1B[1A\x1B[{}D", u16::MAX);
+/// Sends the state to the status bar, which is forgotten after failing so the warning isn't repeated.
+fn send_to_bar(bar_messager: &mut Option<BarMessager>, message: BarMessage) {
+    if let Some(messager) = bar_messager {
+        if messager.send_message(message).is_err() {
+            *bar_messager = None;
+            warn(&TomateError::Io(t().bar_send_failed.to_owned()));
+        }
+    }
+}
+
+/// Erases the help footer drawn by [`Tomato::draw_timer`], leaving the cursor at the start of the timer line.
+fn clear_footer() {
+    print!("\n{CLEAR_LINE}\x1B[1A\r");
 }
 
 #[derive(Clone, Copy)]
+pub enum Display {
+    /// A single line redrawn at every tick, with the key bindings below it.
+    Line,
+    /// Full screen with a big clock, see [`tui::draw`].
+    Tui,
+    /// Nothing redrawn, for when there's no terminal to draw on.
+    Headless,
+}
+
+#[derive(PartialEq)]
+enum TimerEnd {
+    Finished,
+    Skipped,
+    Restarted,
+}
+
+/// Where a stage stopped in the interrupted session, see [`Tomato::resume_from`].
+struct ResumePoint {
+    remaining: Duration,
+    stage_duration: Duration,
+    started_at: DateTime<Local>,
+    paused: bool,
+    /// Counted up after the tomato ended, to continue counting from.
+    overtime: Option<Duration>,
+}
+
+/// Totals of the session so far, shown when quitting.
+#[derive(Default)]
+struct SessionSummary {
+    completed: u32,
+    skipped: u32,
+    focused: Duration,
+    paused: Duration,
+}
+
+impl SessionSummary {
+    fn add(&mut self, stage: Stage, stats: &TimerStats) {
+        self.paused += stats.paused_for;
+
+        if let Stage::Work = stage {
+            self.focused += stats.focused();
+
+            if stats.skipped {
+                self.skipped += 1;
+            } else {
+                self.completed += 1;
+            }
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
 pub enum Stage {
     Work,
     Rest,
 }
 
+impl Stage {
+    /// As written in the history, also passed to the hooks.
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Work => "work",
+            Self::Rest => "rest",
+        }
+    }
+}
+
 impl fmt::Display for Stage {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
         match self {
-            Self::Work => write!(f, "{}", "[Work]".magenta()),
-            Self::Rest => write!(f, "{}", "[Rest]".cyan()),
+            Self::Work => write!(f, "{}", t().stage_work.color(palette().work.0)),
+            Self::Rest => write!(f, "{}", t().stage_rest.color(palette().rest.0)),
         }
     }
 }
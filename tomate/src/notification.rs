@@ -1,9 +1,165 @@
-use crate::error::UnwrapOrExplode;
-
-pub fn send_notification(message: impl AsRef<str>) {
-    notify_rust::Notification::new()
-        .summary("Tomate")
-        .body(message.as_ref())
-        .show()
-        .unwrap_or_explode(format!("Failed to send notification with body: \"{}\"\n", message.as_ref()).as_str());
+use std::{
+    io::{self, Write},
+    os::unix::process::CommandExt,
+    process::{Command, Stdio},
+    thread,
+};
+
+use serde::Deserialize;
+
+use crate::{
+    error::{warn, TomateError},
+    hooks::{self, wait_or_kill},
+    i18n::{render, t},
+    time::describe,
+};
+
+/// Somewhere to show the notifications of the timer.
+pub trait Notifier {
+    fn notify(&self, message: &str) -> io::Result<()>;
+}
+
+/// A popup through the desktop notification daemon.
+pub struct Desktop;
+
+impl Notifier for Desktop {
+    fn notify(&self, message: &str) -> io::Result<()> {
+        notify_rust::Notification::new()
+            .summary("Tomate")
+            .body(message)
+            .show()
+            .map(drop)
+            .map_err(io::Error::other)
+    }
+}
+
+/// Rings the terminal bell, the message itself isn't shown.
+pub struct Bell;
+
+impl Notifier for Bell {
+    fn notify(&self, _: &str) -> io::Result<()> {
+        let mut stdout = io::stdout();
+        stdout.write_all(b"\x07")?;
+        stdout.flush()
+    }
+}
+
+pub struct Stderr;
+
+impl Notifier for Stderr {
+    fn notify(&self, message: &str) -> io::Result<()> {
+        writeln!(io::stderr(), "Tomate: {message}")
+    }
+}
+
+/// Runs a shell command with the message in `$TOMATE_MESSAGE` in the background, like the hooks, so only
+/// failing to start it falls through to the next backend. Exiting with an error or taking longer than the
+/// default timeout of the hooks is only warned about.
+pub struct Hook {
+    pub command: String,
+}
+
+impl Notifier for Hook {
+    fn notify(&self, message: &str) -> io::Result<()> {
+        let mut child = Command::new("sh")
+            .arg("-c")
+            .arg(&self.command)
+            .env("TOMATE_MESSAGE", message)
+            .stdin(Stdio::null())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .process_group(0)
+            .spawn()?;
+
+        let command = self.command.clone();
+        thread::spawn(move || match wait_or_kill(&mut child, hooks::DEFAULT_TIMEOUT) {
+            Some(status) if status.success() => {}
+            Some(status) => warn(&TomateError::Io(render(
+                t().notification_command_failed,
+                &[("command", &command), ("status", &status)],
+            ))),
+            None => warn(&TomateError::Io(render(
+                t().notification_command_timed_out,
+                &[("timeout", &describe(hooks::DEFAULT_TIMEOUT))],
+            ))),
+        });
+
+        Ok(())
+    }
+}
+
+pub struct Silent;
+
+impl Notifier for Silent {
+    fn notify(&self, _: &str) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+/// Tries each notifier in order until one succeeds.
+pub struct Chain(pub Vec<Box<dyn Notifier>>);
+
+impl Notifier for Chain {
+    fn notify(&self, message: &str) -> io::Result<()> {
+        let mut result = Ok(());
+
+        for notifier in &self.0 {
+            result = notifier.notify(message);
+
+            if result.is_ok() {
+                break;
+            }
+        }
+
+        result
+    }
+}
+
+/// The names used by the `backends` key of the `[notifications]` table.
+#[derive(Clone, Copy, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Backend {
+    Desktop,
+    Bell,
+    Stderr,
+    Command,
+    None,
+}
+
+/// The `[notifications]` table of the config file.
+#[derive(Deserialize)]
+#[serde(default)]
+pub struct NotificationConfig {
+    pub backends: Vec<Backend>,
+    pub command: Option<String>,
+}
+
+impl Default for NotificationConfig {
+    fn default() -> Self {
+        Self {
+            backends: vec![Backend::Desktop, Backend::Bell],
+            command: None,
+        }
+    }
+}
+
+impl NotificationConfig {
+    /// Builds the chain of backends, `None` if `command` is listed without a command to run.
+    pub fn build(&self) -> Option<Chain> {
+        let mut notifiers = Vec::<Box<dyn Notifier>>::new();
+
+        for backend in &self.backends {
+            notifiers.push(match backend {
+                Backend::Desktop => Box::new(Desktop),
+                Backend::Bell => Box::new(Bell),
+                Backend::Stderr => Box::new(Stderr),
+                Backend::Command => Box::new(Hook {
+                    command: self.command.clone()?,
+                }),
+                Backend::None => Box::new(Silent),
+            });
+        }
+
+        Some(Chain(notifiers))
+    }
 }
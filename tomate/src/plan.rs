@@ -0,0 +1,397 @@
+use std::{fmt, fs, path::Path, str::FromStr, time::Duration};
+
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    error::{OrError, Result, TomateError},
+    i18n::{render, t},
+    time::{HumanDuration, MINUTE},
+};
+
+const DAY: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// Describes a whole session: how many cycles, their durations and when the long breaks happen.
+#[derive(Clone, Serialize, Deserialize)]
+pub struct SessionPlan {
+    pub cycles: u32,
+    pub work_time: Duration,
+    pub rest_time: Duration,
+    pub long_rest_time: Duration,
+    pub long_break_every: Option<u32>,
+    pub overrides: Vec<CycleOverride>,
+}
+
+impl Default for SessionPlan {
+    fn default() -> Self {
+        Self {
+            cycles: 4,
+            work_time: 25 * MINUTE,
+            rest_time: 5 * MINUTE,
+            long_rest_time: 15 * MINUTE,
+            long_break_every: None,
+            overrides: Vec::new(),
+        }
+    }
+}
+
+impl SessionPlan {
+    pub fn set_cycles(self, cycles: u32) -> Result<Self> {
+        (cycles != 0).or_error(
+            TomateError::Config,
+            &render(t().argument_zero, &[("argument", &"cycles")]),
+        )?;
+
+        Ok(Self { cycles, ..self })
+    }
+
+    pub fn set_work_time(self, work_time: Duration) -> Result<Self> {
+        check_duration(work_time, "work_time")?;
+
+        Ok(Self { work_time, ..self })
+    }
+
+    pub fn set_rest_time(self, rest_time: Duration) -> Result<Self> {
+        check_duration(rest_time, "rest_time")?;
+
+        Ok(Self { rest_time, ..self })
+    }
+
+    pub fn set_long_rest_time(self, long_rest_time: Duration) -> Result<Self> {
+        check_duration(long_rest_time, "long_rest_time")?;
+
+        Ok(Self { long_rest_time, ..self })
+    }
+
+    pub fn set_long_break_every(self, long_break_every: u32) -> Result<Self> {
+        (long_break_every != 0).or_error(
+            TomateError::Config,
+            &render(t().argument_zero, &[("argument", &"long_break_every")]),
+        )?;
+
+        Ok(Self {
+            long_break_every: Some(long_break_every),
+            ..self
+        })
+    }
+
+    pub fn add_override(mut self, cycle_override: CycleOverride) -> Self {
+        self.overrides.push(cycle_override);
+        self
+    }
+
+    /// Checks the overrides against the final number of cycles.
+    pub fn validate(self) -> Result<Self> {
+        for cycle_override in &self.overrides {
+            (1..=self.cycles).contains(&cycle_override.cycle).or_error(
+                TomateError::Config,
+                &render(t().override_outside_plan, &[("cycle", &cycle_override.cycle)]),
+            )?;
+            if let Some(work) = cycle_override.work_time {
+                check_duration(work, "work_time")?;
+            }
+            if let Some(rest) = cycle_override.rest_time {
+                check_duration(rest, "rest_time")?;
+            }
+        }
+
+        Ok(self)
+    }
+
+    /// Resolves the durations of the given cycle, starting from 1.
+    pub fn cycle(&self, number: u32) -> Cycle {
+        let long_break = self.long_break_every.is_some_and(|every| number.is_multiple_of(every));
+
+        let mut cycle = Cycle {
+            number,
+            work_time: self.work_time,
+            rest_time: if long_break {
+                self.long_rest_time
+            } else {
+                self.rest_time
+            },
+            long_break,
+        };
+
+        // Later overrides win
+        for cycle_override in self.overrides.iter().filter(|o| o.cycle == number) {
+            cycle.work_time = cycle_override.work_time.unwrap_or(cycle.work_time);
+            cycle.rest_time = cycle_override.rest_time.unwrap_or(cycle.rest_time);
+        }
+
+        cycle
+    }
+
+    pub fn progress(&self, cycle: &Cycle) -> Progress {
+        Progress {
+            cycle: cycle.number,
+            cycles: self.cycles,
+            long_break: cycle.long_break,
+        }
+    }
+}
+
+/// The keys of a plan file, also used by the `[session]` table of the config file.
+#[derive(Default, Deserialize)]
+#[serde(default)]
+pub struct PlanFile {
+    pub cycles: Option<u32>,
+    pub work: Option<HumanDuration>,
+    pub rest: Option<HumanDuration>,
+    pub long_rest: Option<HumanDuration>,
+    pub long_break_every: Option<u32>,
+    pub cycle: Vec<CycleOverride>,
+}
+
+impl PlanFile {
+    pub fn from_file(path: &Path) -> Result<Self> {
+        let contents = fs::read_to_string(path).or_error(
+            TomateError::Config,
+            &render(t().read_plan_failed, &[("path", &path.display())]),
+        )?;
+
+        let mut unknown_keys = Vec::new();
+        let plan = serde_ignored::deserialize(toml::Deserializer::new(&contents), |key| {
+            unknown_keys.push(key.to_string());
+        })
+        .or_error(
+            TomateError::Config,
+            &render(t().invalid_plan, &[("path", &path.display())]),
+        )?;
+
+        if let Some(key) = unknown_keys.first() {
+            return Err(TomateError::Config(render(
+                t().unknown_plan_key,
+                &[("key", key), ("path", &path.display())],
+            )));
+        }
+
+        Ok(plan)
+    }
+
+    /// Overwrites the values of `plan` with the ones present in this file.
+    pub fn apply(self, mut plan: SessionPlan) -> Result<SessionPlan> {
+        if let Some(cycles) = self.cycles {
+            plan = plan.set_cycles(cycles)?;
+        }
+        if let Some(work) = self.work {
+            plan = plan.set_work_time(work.0)?;
+        }
+        if let Some(rest) = self.rest {
+            plan = plan.set_rest_time(rest.0)?;
+        }
+        if let Some(long_rest) = self.long_rest {
+            plan = plan.set_long_rest_time(long_rest.0)?;
+        }
+        if let Some(every) = self.long_break_every {
+            plan = plan.set_long_break_every(every)?;
+        }
+        for cycle_override in self.cycle {
+            plan = plan.add_override(cycle_override);
+        }
+
+        Ok(plan)
+    }
+}
+
+fn check_duration(duration: Duration, argument: &str) -> Result<()> {
+    (!duration.is_zero()).or_error(
+        TomateError::Config,
+        &render(t().argument_zero, &[("argument", &argument)]),
+    )?;
+
+    (duration <= DAY).or_error(
+        TomateError::Config,
+        &render(t().argument_too_big, &[("argument", &argument)]),
+    )
+}
+
+/// Replaces the durations of a single cycle, parsed from `N:WORK/REST`, where either side can be
+/// omitted, like in `3:50`, `4:/20` or `5:1h30m/15m`.
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct CycleOverride {
+    #[serde(rename = "number")]
+    pub cycle: u32,
+    #[serde(
+        rename = "work",
+        default,
+        serialize_with = "serialize_duration",
+        deserialize_with = "deserialize_duration"
+    )]
+    pub work_time: Option<Duration>,
+    #[serde(
+        rename = "rest",
+        default,
+        serialize_with = "serialize_duration",
+        deserialize_with = "deserialize_duration"
+    )]
+    pub rest_time: Option<Duration>,
+}
+
+/// As seconds with a unit, like `90s`, so it's read back the same by [`deserialize_duration`].
+fn serialize_duration<S: serde::Serializer>(duration: &Option<Duration>, serializer: S) -> Result<S::Ok, S::Error> {
+    duration
+        .map(|duration| format!("{}s", duration.as_secs()))
+        .serialize(serializer)
+}
+
+fn deserialize_duration<'de, D: serde::Deserializer<'de>>(deserializer: D) -> Result<Option<Duration>, D::Error> {
+    Option::<HumanDuration>::deserialize(deserializer).map(|duration| duration.map(|duration| duration.0))
+}
+
+impl FromStr for CycleOverride {
+    type Err = String;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let parse_duration = |text: &str| -> Result<Option<Duration>, String> {
+            match text {
+                "" => Ok(None),
+                text => text.parse().map(|duration: HumanDuration| Some(duration.0)),
+            }
+        };
+
+        let (cycle, times) = text.split_once(':').ok_or(t().invalid_override_format)?;
+        let (work, rest) = times.split_once('/').unwrap_or((times, ""));
+
+        Ok(Self {
+            cycle: cycle
+                .parse()
+                .map_err(|_| render(t().invalid_cycle_number, &[("cycle", &cycle)]))?,
+            work_time: parse_duration(work)?,
+            rest_time: parse_duration(rest)?,
+        })
+    }
+}
+
+#[derive(Clone, Copy)]
+pub struct Cycle {
+    pub number: u32,
+    pub work_time: Duration,
+    pub rest_time: Duration,
+    pub long_break: bool,
+}
+
+/// Where the session is at, displayed as "cycle 3/6, long break next".
+#[derive(Clone, Copy)]
+pub struct Progress {
+    pub cycle: u32,
+    pub cycles: u32,
+    pub long_break: bool,
+}
+
+impl fmt::Display for Progress {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let Self {
+            cycle,
+            cycles,
+            long_break,
+        } = self;
+
+        write!(f, "{}", render(t().progress, &[("cycle", cycle), ("cycles", cycles)]))?;
+
+        if *long_break {
+            write!(f, "{}", t().long_break_next)?;
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn cycle_override(text: &str) -> CycleOverride {
+        text.parse().unwrap()
+    }
+
+    #[track_caller]
+    fn config_error(result: Result<SessionPlan>) -> String {
+        match result {
+            Err(TomateError::Config(message)) => message,
+            _ => panic!("expected a config error"),
+        }
+    }
+
+    #[test]
+    fn parses_overrides() {
+        let both = cycle_override("5:1h30m/15m");
+        assert_eq!(
+            (both.cycle, both.work_time, both.rest_time),
+            (5, Some(90 * MINUTE), Some(15 * MINUTE))
+        );
+
+        let work = cycle_override("3:50");
+        assert_eq!(
+            (work.cycle, work.work_time, work.rest_time),
+            (3, Some(50 * MINUTE), None)
+        );
+
+        let rest = cycle_override("4:/20");
+        assert_eq!(
+            (rest.cycle, rest.work_time, rest.rest_time),
+            (4, None, Some(20 * MINUTE))
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_overrides() {
+        let error = |text: &str| text.parse::<CycleOverride>().err().unwrap();
+
+        assert_eq!(error("350"), t().invalid_override_format);
+        assert_eq!(error("x:50"), render(t().invalid_cycle_number, &[("cycle", &"x")]));
+        assert_eq!(error("3:fifty"), render(t().invalid_duration, &[("text", &"fifty")]));
+    }
+
+    #[test]
+    fn resolves_the_cycles() {
+        let plan = SessionPlan::default()
+            .set_long_break_every(2)
+            .unwrap()
+            .add_override(cycle_override("3:50"))
+            .add_override(cycle_override("4:/20"))
+            .add_override(cycle_override("4:10"));
+
+        let cycle = |number| {
+            let cycle = plan.cycle(number);
+            (cycle.work_time, cycle.rest_time, cycle.long_break)
+        };
+
+        assert_eq!(cycle(1), (25 * MINUTE, 5 * MINUTE, false));
+        assert_eq!(cycle(2), (25 * MINUTE, 15 * MINUTE, true));
+        assert_eq!(cycle(3), (50 * MINUTE, 5 * MINUTE, false));
+        // Both overrides apply, the later one on top
+        assert_eq!(cycle(4), (10 * MINUTE, 20 * MINUTE, true));
+    }
+
+    #[test]
+    fn rejects_overrides_outside_the_plan() {
+        let plan = |text| SessionPlan::default().add_override(cycle_override(text)).validate();
+
+        assert!(plan("4:50").is_ok());
+        for text in ["5:50", "0:50"] {
+            let cycle = cycle_override(text).cycle;
+            assert_eq!(
+                config_error(plan(text)),
+                render(t().override_outside_plan, &[("cycle", &cycle)])
+            );
+        }
+    }
+
+    #[test]
+    fn durations_from_a_second_to_a_day() {
+        let too_short = render(t().argument_zero, &[("argument", &"work_time")]);
+        let too_long = render(t().argument_too_big, &[("argument", &"work_time")]);
+        let work_time = |duration| SessionPlan::default().set_work_time(duration);
+
+        assert!(work_time(Duration::from_secs(1)).is_ok());
+        assert!(work_time(DAY).is_ok());
+        assert_eq!(config_error(work_time(Duration::ZERO)), too_short);
+        assert_eq!(config_error(work_time(DAY + Duration::from_secs(1))), too_long);
+
+        // The overrides too, once the plan is complete
+        let plan = |text| SessionPlan::default().add_override(cycle_override(text)).validate();
+        assert_eq!(config_error(plan("2:0")), too_short);
+        assert_eq!(config_error(plan("2:24h1s")), too_long);
+    }
+}
@@ -0,0 +1,122 @@
+use std::io::{self, Write};
+
+use serde::Deserialize;
+
+#[derive(Clone, Copy)]
+pub enum Cue {
+    WorkStart,
+    Halfway,
+    RestEnd,
+}
+
+/// The `[sounds]` table of the config file.
+#[derive(Clone, Copy, Deserialize)]
+#[serde(default)]
+pub struct SoundConfig {
+    /// From 0 to 1, only applies to the bundled tones, not to the terminal bell.
+    pub volume: f32,
+    pub work_start: bool,
+    pub halfway: bool,
+    pub rest_end: bool,
+}
+
+impl Default for SoundConfig {
+    fn default() -> Self {
+        Self {
+            volume: 1.0,
+            work_start: true,
+            halfway: true,
+            rest_end: true,
+        }
+    }
+}
+
+impl SoundConfig {
+    fn is_enabled(&self, cue: Cue) -> bool {
+        match cue {
+            Cue::WorkStart => self.work_start,
+            Cue::Halfway => self.halfway,
+            Cue::RestEnd => self.rest_end,
+        }
+    }
+}
+
+/// Plays the cues enabled in the config.
+pub struct Sounds {
+    config: SoundConfig,
+    player: Player,
+}
+
+impl Sounds {
+    pub fn new(config: SoundConfig) -> Self {
+        Self {
+            config,
+            player: Player::new(),
+        }
+    }
+
+    pub fn play(&self, cue: Cue) {
+        if self.config.is_enabled(cue) && self.player.play(cue, self.config.volume).is_err() {
+            ring_bell();
+        }
+    }
+}
+
+/// Fallback for when there's no audio, or the `sound` feature is disabled.
+fn ring_bell() {
+    let mut stdout = io::stdout();
+    let _ = stdout.write_all(b"\x07").and_then(|()| stdout.flush());
+}
+
+cfg_if::cfg_if! {
+    if #[cfg(feature = "sound")] {
+        use std::io::Cursor;
+
+        use rodio::{Decoder, OutputStream, OutputStreamHandle, Sink};
+
+        const WORK_START: &[u8] = include_bytes!("../assets/work_start.wav");
+        const HALFWAY: &[u8] = include_bytes!("../assets/halfway.wav");
+        const REST_END: &[u8] = include_bytes!("../assets/rest_end.wav");
+
+        struct Player {
+            // The stream stops playing when dropped, `None` if there's no audio device
+            output: Option<(OutputStream, OutputStreamHandle)>,
+        }
+
+        impl Player {
+            fn new() -> Self {
+                Self { output: OutputStream::try_default().ok() }
+            }
+
+            fn play(&self, cue: Cue, volume: f32) -> Result<(), ()> {
+                let (_, handle) = self.output.as_ref().ok_or(())?;
+
+                let bytes = match cue {
+                    Cue::WorkStart => WORK_START,
+                    Cue::Halfway => HALFWAY,
+                    Cue::RestEnd => REST_END,
+                };
+
+                let sink = Sink::try_new(handle).map_err(drop)?;
+                sink.set_volume(volume);
+                sink.append(Decoder::new(Cursor::new(bytes)).map_err(drop)?);
+                // Keeps playing in the background
+                sink.detach();
+
+                Ok(())
+            }
+        }
+    } else {
+        struct Player;
+
+        impl Player {
+            fn new() -> Self {
+                Self
+            }
+
+            fn play(&self, _: Cue, _: f32) -> Result<(), ()> {
+                Err(())
+            }
+        }
+    }
+}
@@ -0,0 +1,516 @@
+use std::{collections::BTreeMap, fs, io, path::PathBuf};
+
+use chrono::{Datelike, Days, Local, NaiveDate};
+use clap::{Args, ValueEnum};
+use owo_colors::OwoColorize;
+use serde::Serialize;
+
+use crate::{
+    colors::palette,
+    error::{OrError, Result, TomateError},
+    history::{DateRange, Record},
+    i18n::{render, t},
+    show, showln,
+    time::Time,
+    Stage,
+};
+
+const HEATMAP_WEEKS: u64 = 20;
+const RECENT_DAYS: usize = 14;
+const RECENT_WEEKS: usize = 8;
+
+#[derive(Args)]
+pub struct StatsArgs {
+    #[command(flatten)]
+    range: DateRange,
+    /// Export the report instead of displaying it.
+    #[arg(long, value_enum, value_name = "FORMAT")]
+    export: Option<ExportFormat>,
+    /// Write the export to this file instead of STDOUT.
+    #[arg(short, long, value_name = "FILE", requires = "export")]
+    output: Option<PathBuf>,
+}
+
+#[derive(Clone, Copy, ValueEnum)]
+enum ExportFormat {
+    Csv,
+    Json,
+}
+
+#[derive(Serialize)]
+struct Report {
+    completed: u32,
+    skipped: u32,
+    completion_ratio: f64,
+    average_pause_secs: u64,
+    longest_streak: u32,
+    days: Vec<DayStats>,
+    weeks: Vec<WeekStats>,
+    tasks: Vec<TaskStats>,
+}
+
+#[derive(Serialize)]
+struct DayStats {
+    date: NaiveDate,
+    focused_secs: u64,
+    completed: u32,
+    skipped: u32,
+    paused_secs: u64,
+}
+
+/// Work done under a task label, `None` groups the tomatoes without one.
+#[derive(Serialize)]
+struct TaskStats {
+    task: Option<String>,
+    focused_secs: u64,
+    completed: u32,
+    skipped: u32,
+}
+
+#[derive(Serialize)]
+struct WeekStats {
+    year: i32,
+    week: u32,
+    focused_secs: u64,
+    completed: u32,
+}
+
+impl Report {
+    fn new(records: &[Record]) -> Self {
+        let mut days = BTreeMap::<NaiveDate, DayStats>::new();
+        let mut tasks = BTreeMap::<Option<String>, TaskStats>::new();
+        let mut pauses = 0;
+        let mut paused_secs = 0;
+
+        for record in records {
+            pauses += record.pauses;
+            paused_secs += record.paused_secs;
+
+            let date = record.date();
+            let day = days.entry(date).or_insert_with(|| DayStats {
+                date,
+                focused_secs: 0,
+                completed: 0,
+                skipped: 0,
+                paused_secs: 0,
+            });
+
+            day.paused_secs += record.paused_secs;
+
+            if let Stage::Work = record.stage {
+                let task = tasks.entry(record.task.clone()).or_insert_with(|| TaskStats {
+                    task: record.task.clone(),
+                    focused_secs: 0,
+                    completed: 0,
+                    skipped: 0,
+                });
+
+                day.focused_secs += record.focused_secs();
+                task.focused_secs += record.focused_secs();
+
+                if record.skipped {
+                    day.skipped += 1;
+                    task.skipped += 1;
+                } else {
+                    day.completed += 1;
+                    task.completed += 1;
+                }
+            }
+        }
+
+        let mut weeks = BTreeMap::<(i32, u32), WeekStats>::new();
+
+        for day in days.values() {
+            let iso_week = day.date.iso_week();
+            let week = weeks
+                .entry((iso_week.year(), iso_week.week()))
+                .or_insert_with(|| WeekStats {
+                    year: iso_week.year(),
+                    week: iso_week.week(),
+                    focused_secs: 0,
+                    completed: 0,
+                });
+
+            week.focused_secs += day.focused_secs;
+            week.completed += day.completed;
+        }
+
+        let completed = days.values().map(|day| day.completed).sum();
+        let skipped = days.values().map(|day| day.skipped).sum();
+        let total = completed + skipped;
+
+        Self {
+            completed,
+            skipped,
+            completion_ratio: if total == 0 {
+                0.0
+            } else {
+                completed as f64 / total as f64
+            },
+            average_pause_secs: if pauses == 0 { 0 } else { paused_secs / pauses as u64 },
+            longest_streak: longest_streak(days.values()),
+            days: days.into_values().collect(),
+            weeks: weeks.into_values().collect(),
+            tasks: tasks.into_values().collect(),
+        }
+    }
+
+    fn focused_secs_at(&self, date: NaiveDate) -> u64 {
+        self.days
+            .binary_search_by_key(&date, |day| day.date)
+            .map_or(0, |index| self.days[index].focused_secs)
+    }
+}
+
+/// Longest run of consecutive days with at least one completed tomato.
+fn longest_streak<'a>(days: impl Iterator<Item = &'a DayStats>) -> u32 {
+    let mut longest = 0;
+    let mut current = 0;
+    let mut last_date: Option<NaiveDate> = None;
+
+    for day in days.filter(|day| day.completed > 0) {
+        let is_consecutive = last_date.is_some_and(|last| last.succ_opt() == Some(day.date));
+        current = if is_consecutive { current + 1 } else { 1 };
+        longest = longest.max(current);
+        last_date = Some(day.date);
+    }
+
+    longest
+}
+
+pub fn show_stats(args: StatsArgs) -> Result<()> {
+    let report = Report::new(&args.range.load()?);
+
+    match args.export {
+        Some(format) => export(&report, format, args.output),
+        None => {
+            display(&report, args.range.until.unwrap_or_else(|| Local::now().date_naive()));
+            Ok(())
+        }
+    }
+}
+
+fn export(report: &Report, format: ExportFormat, output: Option<PathBuf>) -> Result<()> {
+    let contents = match format {
+        ExportFormat::Json => {
+            serde_json::to_string_pretty(report).or_error(TomateError::Io, t().serialize_report_failed)?
+        }
+        ExportFormat::Csv => {
+            let mut csv = String::from("date,focused_minutes,completed,skipped,paused_minutes\n");
+
+            for day in &report.days {
+                csv += &format!(
+                    "{},{},{},{},{}\n",
+                    day.date,
+                    day.focused_secs / 60,
+                    day.completed,
+                    day.skipped,
+                    day.paused_secs / 60,
+                );
+            }
+
+            csv
+        }
+    };
+
+    match output {
+        Some(path) => fs::write(&path, contents).or_error(
+            TomateError::Io,
+            &render(t().write_report_failed, &[("path", &path.display())]),
+        ),
+        None => io::Write::write_all(&mut io::stdout(), contents.as_bytes()).or_error(
+            TomateError::Io,
+            &render(t().write_report_failed, &[("path", &"STDOUT")]),
+        ),
+    }
+}
+
+fn display(report: &Report, last_date: NaiveDate) {
+    if report.days.is_empty() {
+        showln!(t().no_records.dimmed());
+        return;
+    }
+
+    let completed = render(t().stats_completed, &[("count", &report.completed)]);
+    let skipped = render(t().stats_skipped, &[("count", &report.skipped)]);
+    let ratio = format!("{:.0}", report.completion_ratio * 100.0);
+    let average_pause = Time::from(report.average_pause_secs as u32);
+    let streak = render(t().days, &[("count", &report.longest_streak)]);
+
+    showln!(
+        "  ",
+        render(
+            t().stats_tomatoes,
+            &[
+                ("completed", &completed.green()),
+                ("skipped", &skipped.red()),
+                ("ratio", &ratio)
+            ],
+        ),
+    );
+    showln!(
+        "  ",
+        render(
+            t().stats_average_pause,
+            &[("time", &average_pause.color(palette().time.0))]
+        ),
+    );
+    showln!(
+        "  ",
+        render(t().stats_longest_streak, &[("days", &streak.color(palette().time.0))]),
+    );
+
+    showln!("\n  ", t().per_day);
+    for day in report.days.iter().rev().take(RECENT_DAYS).rev() {
+        showln!(
+            format_args!("    {}  ", day.date),
+            format_args!("{:>4} min", day.focused_secs / 60).color(palette().time.0),
+            "  ",
+            render(t().tomato_count, &[("count", &day.completed)]).dimmed(),
+        );
+    }
+
+    showln!("\n  ", t().per_week);
+    for week in report.weeks.iter().rev().take(RECENT_WEEKS).rev() {
+        let week_number = format!("{:02}", week.week);
+
+        showln!(
+            "    ",
+            render(t().week_label, &[("year", &week.year), ("week", &week_number)]),
+            "  ",
+            format_args!("{:>4} min", week.focused_secs / 60).color(palette().time.0),
+            "  ",
+            render(t().tomato_count, &[("count", &week.completed)]).dimmed(),
+        );
+    }
+
+    showln!("\n  ", t().per_task);
+    for task in &report.tasks {
+        let label = task.task.as_deref().unwrap_or(t().no_task);
+
+        showln!(
+            format_args!("    {label}  ").color(palette().task.0),
+            format_args!("{} min", task.focused_secs / 60).color(palette().time.0),
+            "  ",
+            render(
+                t().task_count,
+                &[("completed", &task.completed), ("skipped", &task.skipped)]
+            )
+            .dimmed(),
+        );
+    }
+
+    println!();
+    display_heatmap(report, last_date);
+}
+
+/// How hot a cell of the heatmap is, from 0 for nothing to 4 for more than 150 minutes, see the legend.
+fn heat(focused_minutes: u64) -> u8 {
+    match focused_minutes {
+        0 => 0,
+        1..=25 => 1,
+        26..=75 => 2,
+        76..=150 => 3,
+        _ => 4,
+    }
+}
+
+/// GitHub-like grid of focused minutes, one column per week and one row per weekday.
+fn display_heatmap(report: &Report, last_date: NaiveDate) {
+    let last_monday = last_date - Days::new(last_date.weekday().num_days_from_monday() as u64);
+    let first_monday = last_monday - Days::new(7 * (HEATMAP_WEEKS - 1));
+
+    for (weekday, label) in t().weekdays.iter().enumerate() {
+        show!(format_args!("  {label} "));
+
+        for week in 0..HEATMAP_WEEKS {
+            let date = first_monday + Days::new(week * 7 + weekday as u64);
+
+            if date > last_date {
+                break;
+            }
+
+            let cell = match heat(report.focused_secs_at(date) / 60) {
+                0 => " ·".dimmed().to_string(),
+                1 => " ■".red().to_string(),
+                2 => " ■".yellow().to_string(),
+                3 => " ■".green().to_string(),
+                _ => " ■".bright_green().to_string(),
+            };
+
+            print!("{cell}");
+        }
+
+        println!();
+    }
+
+    showln!(
+        "\n  ",
+        "· 0".dimmed(),
+        "  ",
+        "■ 1-25".red(),
+        "  ",
+        "■ 26-75".yellow(),
+        "  ",
+        "■ 76-150".green(),
+        "  ",
+        "■ 150+ min".bright_green(),
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use chrono::{DateTime, TimeZone};
+
+    use super::*;
+
+    fn date(text: &str) -> NaiveDate {
+        text.parse().unwrap()
+    }
+
+    /// At noon, far from any change of the time zone.
+    fn noon(day: &str) -> DateTime<Local> {
+        Local
+            .from_local_datetime(&date(day).and_hms_opt(12, 0, 0).unwrap())
+            .unwrap()
+    }
+
+    fn work(day: &str, task: Option<&str>, minutes: u64) -> Record {
+        Record {
+            stage: Stage::Work,
+            tomato: 1,
+            task: task.map(str::to_owned),
+            started_at: noon(day),
+            ended_at: noon(day),
+            planned_secs: 25 * 60,
+            actual_secs: minutes * 60,
+            paused_secs: 0,
+            pauses: 0,
+            idle_secs: 0,
+            overtime_secs: 0,
+            skipped: false,
+            activity: None,
+            activity_done: None,
+        }
+    }
+
+    fn skipped(day: &str, task: Option<&str>, minutes: u64) -> Record {
+        Record {
+            skipped: true,
+            ..work(day, task, minutes)
+        }
+    }
+
+    fn rest(day: &str, paused_secs: u64, pauses: u32) -> Record {
+        Record {
+            stage: Stage::Rest,
+            paused_secs,
+            pauses,
+            ..work(day, None, 5)
+        }
+    }
+
+    #[test]
+    fn adds_up_days_weeks_and_tasks() {
+        let report = Report::new(&[
+            // Monday and Tuesday of the same week
+            Record {
+                overtime_secs: 5 * 60,
+                paused_secs: 120,
+                pauses: 2,
+                ..work("2026-10-12", Some("docs"), 25)
+            },
+            rest("2026-10-12", 60, 1),
+            skipped("2026-10-12", None, 10),
+            work("2026-10-13", Some("docs"), 25),
+            // Monday of the next one
+            work("2026-10-19", None, 50),
+        ]);
+
+        assert_eq!((report.completed, report.skipped), (3, 1));
+        assert_eq!(report.completion_ratio, 0.75);
+        // Of the rests too
+        assert_eq!(report.average_pause_secs, 60);
+
+        let days: Vec<_> = report
+            .days
+            .iter()
+            .map(|day| {
+                (
+                    day.date,
+                    day.focused_secs / 60,
+                    day.completed,
+                    day.skipped,
+                    day.paused_secs,
+                )
+            })
+            .collect();
+        assert_eq!(
+            days,
+            [
+                (date("2026-10-12"), 40, 1, 1, 180),
+                (date("2026-10-13"), 25, 1, 0, 0),
+                (date("2026-10-19"), 50, 1, 0, 0),
+            ]
+        );
+
+        let weeks: Vec<_> = report
+            .weeks
+            .iter()
+            .map(|week| (week.year, week.week, week.focused_secs / 60, week.completed))
+            .collect();
+        assert_eq!(weeks, [(2026, 42, 65, 2), (2026, 43, 50, 1)]);
+
+        let tasks: Vec<_> = report
+            .tasks
+            .iter()
+            .map(|task| {
+                (
+                    task.task.as_deref(),
+                    task.focused_secs / 60,
+                    task.completed,
+                    task.skipped,
+                )
+            })
+            .collect();
+        assert_eq!(tasks, [(None, 60, 1, 1), (Some("docs"), 55, 2, 0)]);
+
+        assert_eq!(report.focused_secs_at(date("2026-10-13")), 25 * 60);
+        assert_eq!(report.focused_secs_at(date("2026-10-14")), 0);
+    }
+
+    #[test]
+    fn nothing_to_report() {
+        let report = Report::new(&[]);
+
+        assert_eq!((report.completed, report.skipped, report.longest_streak), (0, 0, 0));
+        assert_eq!(report.completion_ratio, 0.0);
+        assert_eq!(report.average_pause_secs, 0);
+    }
+
+    #[test]
+    fn streak_of_days_with_a_completed_tomato() {
+        let report = Report::new(&[
+            work("2026-10-01", None, 25),
+            work("2026-10-02", None, 25),
+            // A gap
+            work("2026-10-04", None, 25),
+            work("2026-10-05", None, 25),
+            work("2026-10-05", None, 25),
+            work("2026-10-06", None, 25),
+            // Only skipped, or only resting, doesn't count
+            skipped("2026-10-07", None, 10),
+            rest("2026-10-08", 0, 0),
+            work("2026-10-09", None, 25),
+        ]);
+
+        assert_eq!(report.longest_streak, 3);
+    }
+
+    #[test]
+    fn heat_follows_the_legend() {
+        let heats: Vec<_> = [0, 1, 25, 26, 75, 76, 150, 151, 600].into_iter().map(heat).collect();
+
+        assert_eq!(heats, [0, 1, 1, 2, 2, 3, 3, 4, 4]);
+    }
+}
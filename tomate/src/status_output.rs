@@ -0,0 +1,237 @@
+use std::{
+    fs::{self, File, OpenOptions},
+    io::{self, Write},
+    os::unix::{fs::FileTypeExt, io::FromRawFd},
+    path::{Path, PathBuf},
+    time::Duration,
+};
+
+use clap::{Args, ValueEnum};
+use serde::Serialize;
+
+use crate::{
+    control::Status,
+    error::{OrError, OrWarn, Result, TomateError},
+    i18n::{render, t},
+    time::Time,
+    Stage,
+};
+
+#[derive(Clone, Copy, ValueEnum)]
+pub enum StatusFormat {
+    /// A line with the same object answered by `tomate ctl status --json`.
+    Json,
+    /// The protocol of `status_command` in i3bar and swaybar.
+    I3bar,
+    /// A line for a waybar custom module with `"return-type": "json"`.
+    Waybar,
+}
+
+/// Where the state of the timer is written at every tick, for status bars without climsg.
+#[derive(Args)]
+pub struct StatusArgs {
+    /// Write the state of the timer at every tick, to STDOUT unless `--status-file` is given.
+    #[arg(long, value_enum, value_name = "FORMAT")]
+    status_format: Option<StatusFormat>,
+    /// A file, rewritten at every tick, or a FIFO, to write the state to instead of STDOUT.
+    #[arg(long, value_name = "PATH", requires = "status_format")]
+    status_file: Option<PathBuf>,
+}
+
+impl StatusArgs {
+    /// Whether the state goes to STDOUT, leaving no room for drawing the timer there.
+    pub fn takes_stdout(&self) -> bool {
+        self.status_format.is_some() && self.status_file.is_none()
+    }
+
+    pub fn open(&self) -> Result<Option<StatusWriter>> {
+        let Some(format) = self.status_format else {
+            return Ok(None);
+        };
+
+        StatusWriter::open(format, self.status_file.as_deref()).map(Some)
+    }
+}
+
+enum Output {
+    /// STDOUT or a FIFO, a line is appended at every tick.
+    Stream(Box<dyn Write>),
+    /// A regular file holding only the latest line, for bars that poll it.
+    File(PathBuf),
+}
+
+pub struct StatusWriter {
+    format: StatusFormat,
+    /// `None` after failing to write, so the warning isn't repeated at every tick.
+    output: Option<Output>,
+}
+
+impl StatusWriter {
+    fn open(format: StatusFormat, path: Option<&Path>) -> Result<Self> {
+        let mut output = match path {
+            None => Output::Stream(Box::new(take_stdout()?)),
+            Some(path) if is_fifo(path) => {
+                // Blocks until the bar opens the other end
+                let fifo = OpenOptions::new().write(true).open(path).or_error(
+                    TomateError::Io,
+                    &render(t().status_file_failed, &[("path", &path.display())]),
+                )?;
+                Output::Stream(Box::new(fifo))
+            }
+            Some(path) => Output::File(path.to_owned()),
+        };
+
+        if let (StatusFormat::I3bar, Output::Stream(stream)) = (format, &mut output) {
+            // The header, then an endless array with the blocks at every tick
+            let _ = stream.write_all(b"{\"version\":1}\n[\n");
+        }
+
+        Ok(Self {
+            format,
+            output: Some(output),
+        })
+    }
+
+    /// Writes the state, `done` is how much of the current stage is done, from 0 to 1.
+    pub fn write(&mut self, status: &Status, done: f64) {
+        let Some(output) = &mut self.output else {
+            return;
+        };
+
+        let line = match self.format {
+            StatusFormat::Json => serde_json::to_string(status),
+            StatusFormat::I3bar => serde_json::to_string(&[I3barBlock::new(status)]),
+            StatusFormat::Waybar => serde_json::to_string(&WaybarModule::new(status, done)),
+        }
+        .unwrap();
+
+        let result = match output {
+            Output::Stream(stream) => {
+                let separator = if let StatusFormat::I3bar = self.format { "," } else { "" };
+                writeln!(stream, "{line}{separator}").and_then(|()| stream.flush())
+            }
+            Output::File(path) => fs::write(path, line + "\n"),
+        };
+
+        if result
+            .or_error(TomateError::Io, t().status_write_failed)
+            .or_warn()
+            .is_none()
+        {
+            self.output = None;
+        }
+    }
+
+    /// Writes an idle state, hiding the timer from the bar at the end of the session.
+    pub fn finish(&mut self) {
+        self.write(&Status::default(), 0.0);
+    }
+}
+
+#[derive(Serialize)]
+struct I3barBlock {
+    name: &'static str,
+    instance: &'static str,
+    full_text: String,
+    short_text: String,
+}
+
+impl I3barBlock {
+    fn new(status: &Status) -> Self {
+        Self {
+            name: "tomate",
+            instance: status.stage.map_or("idle", Stage::name),
+            full_text: text(status),
+            short_text: status.stage.map(|_| remaining(status)).unwrap_or_default(),
+        }
+    }
+}
+
+#[derive(Serialize)]
+struct WaybarModule {
+    text: String,
+    tooltip: String,
+    alt: &'static str,
+    /// The stage, and `paused` if it is, to be styled in the waybar CSS.
+    class: Vec<&'static str>,
+    percentage: u32,
+}
+
+impl WaybarModule {
+    fn new(status: &Status, done: f64) -> Self {
+        let mut class = vec![status.stage.map_or("idle", Stage::name)];
+        if status.paused {
+            class.push("paused");
+        }
+        if status.overtime_secs > 0 {
+            class.push("overtime");
+        }
+
+        Self {
+            text: text(status),
+            tooltip: status.task.clone().unwrap_or_default(),
+            alt: class[0],
+            class,
+            percentage: (done.clamp(0.0, 1.0) * 100.0).round() as u32,
+        }
+    }
+}
+
+/// Like the climsg bar, "work 12:34 (1/4)" or "work +01:23 (1/4) · 5/8 today", empty when there's no timer running.
+fn text(status: &Status) -> String {
+    let Some(stage) = status.stage else {
+        return String::new();
+    };
+
+    let stage = match stage {
+        Stage::Work => t().bar_work,
+        Stage::Rest => t().bar_rest,
+    };
+    let paused = if status.paused {
+        format!(" {}", t().paused)
+    } else {
+        String::new()
+    };
+
+    let goal = status
+        .goal
+        .as_ref()
+        .map(|goal| format!(" · {goal}"))
+        .unwrap_or_default();
+
+    format!(
+        "{stage} {} ({}/{}){goal}{paused}",
+        remaining(status),
+        status.cycle,
+        status.cycles
+    )
+}
+
+/// The time left, or the overtime with a plus sign.
+fn remaining(status: &Status) -> String {
+    if status.overtime_secs > 0 {
+        format!("+{}", Time::from(Duration::from_secs(status.overtime_secs)))
+    } else {
+        Time::from(Duration::from_secs(status.remaining_secs)).to_string()
+    }
+}
+
+fn is_fifo(path: &Path) -> bool {
+    fs::metadata(path).is_ok_and(|metadata| metadata.file_type().is_fifo())
+}
+
+/// Keeps the real STDOUT for the state, pointing it to STDERR so everything else printed goes there.
+fn take_stdout() -> Result<File> {
+    let _ = io::stdout().flush();
+
+    let fd = unsafe { libc::dup(libc::STDOUT_FILENO) };
+    (fd >= 0).or_error(
+        TomateError::Terminal,
+        &render(t().status_file_failed, &[("path", &"STDOUT")]),
+    )?;
+
+    unsafe {
+        libc::dup2(libc::STDERR_FILENO, libc::STDOUT_FILENO);
+        Ok(File::from_raw_fd(fd))
+    }
+}
@@ -1,35 +1,56 @@
 use std::{
-    io::{self, BufRead},
+    io::{self, Read},
     mem,
     sync::mpsc::{self, Receiver, SyncSender},
     thread,
 };
 
-use crate::error::UnwrapOrExplode;
+use signal_hook::{consts::SIGWINCH, iterator::Signals};
 
-pub fn spawn_stdin_channel() -> Receiver<String> {
+use crate::control::Input;
+
+pub fn spawn_stdin_channel() -> Receiver<Input> {
     // Create a Rendezvous Channel (backpressure with N = 0)
-    let (stdin_tx, stdin_rx) = mpsc::sync_channel::<String>(0);
+    let (stdin_tx, stdin_rx) = mpsc::sync_channel::<Input>(0);
 
     // For convenience on the receiver side, turn the channel impossible to close.
     mem::forget(stdin_tx.clone());
 
+    let resize_tx = stdin_tx.clone();
     thread::spawn(|| run_stdin_reader(stdin_tx));
+    thread::spawn(|| forward_resizes(resize_tx));
 
     stdin_rx
 }
 
-fn run_stdin_reader(sender: SyncSender<String>) {
+/// Sends every byte as a key, in raw mode they arrive as soon as they're pressed.
+fn run_stdin_reader(sender: SyncSender<Input>) {
     let mut stdin = io::stdin().lock();
+    let mut buffer = [0; 64];
 
     loop {
-        let mut string = String::new();
-
-        match stdin.read_line(&mut string) {
+        let read = match stdin.read(&mut buffer) {
             Ok(0) | Err(_) => return,
-            _ => {}
+            Ok(read) => read,
+        };
+
+        for &key in &buffer[..read] {
+            // Nobody is listening anymore, the session is over
+            if sender.send(Input::Key(key)).is_err() {
+                return;
+            }
         }
+    }
+}
+
+/// Sends an [`Input::Resize`] for every `SIGWINCH`, so the timer is redrawn right away.
+fn forward_resizes(sender: SyncSender<Input>) {
+    let Ok(mut signals) = Signals::new([SIGWINCH]) else {
+        return;
+    };
 
-        sender.send(string).unwrap_or_explode("Internal STDIN sender failed");
+    for _ in signals.forever() {
+        // The timer only stops receiving once the session is over
+        let _ = sender.send(Input::Resize);
     }
 }
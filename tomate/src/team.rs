@@ -0,0 +1,350 @@
+use std::{
+    env,
+    io::{self, Write},
+    net::{TcpListener, TcpStream},
+    sync::{
+        mpsc::{self, Receiver, SyncSender},
+        Arc, Mutex,
+    },
+    thread,
+    time::Duration,
+};
+
+use clap::Args;
+use owo_colors::OwoColorize;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    clear_footer,
+    colors::palette,
+    control::{input_channel, Control, Input, Request, SharedStatus, Status},
+    error::{warn, OrError, Result, TomateError},
+    i18n::{render, t},
+    json_lines,
+    notification::{Chain, Notifier},
+    showln,
+    stdin::spawn_stdin_channel,
+    terminal,
+    time::Time,
+    Stage, CLEAR_LINE,
+};
+
+/// Only this machine, since anyone who can connect can pause or skip the session.
+const DEFAULT_ADDRESS: &str = "127.0.0.1:7878";
+/// How often the state of the host is checked for changes to be sent to the members.
+const BROADCAST_INTERVAL: Duration = Duration::from_millis(200);
+/// How long a member that stopped reading can hold up the others before being dropped.
+const WRITE_TIMEOUT: Duration = Duration::from_secs(1);
+
+/// Names of everyone in the session, the host first.
+pub type Participants = Arc<Mutex<Vec<String>>>;
+
+#[derive(Args)]
+pub struct HostArgs {
+    /// Address to listen on for the members, `127.0.0.1:7878` by default, like `0.0.0.0:7878` for the
+    /// whole network.
+    #[arg(long, value_name = "ADDRESS")]
+    listen: Option<String>,
+    #[command(flatten)]
+    name: NameArgs,
+}
+
+#[derive(Args)]
+pub struct JoinArgs {
+    /// Address of the host, like `192.168.0.10:7878`.
+    address: String,
+    #[command(flatten)]
+    name: NameArgs,
+}
+
+#[derive(Args)]
+struct NameArgs {
+    /// How you're shown to the others, `$USER` by default.
+    #[arg(long)]
+    name: Option<String>,
+}
+
+impl NameArgs {
+    fn name(&self) -> String {
+        self.name
+            .clone()
+            .or_else(|| env::var("USER").ok())
+            .unwrap_or_else(|| t().team_anonymous.to_owned())
+    }
+}
+
+/// A line sent by a member to the host.
+#[derive(Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+enum MemberMessage {
+    Hello { name: String },
+    Control { request: Request },
+}
+
+/// A line sent by the host to every member.
+#[derive(Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "lowercase")]
+enum HostMessage {
+    State { status: Status, participants: Vec<String> },
+    Action { name: String, request: Request },
+}
+
+type Members = Arc<Mutex<Vec<(u64, TcpStream)>>>;
+
+/// Starts serving the session, returning the keys of the host merged with the requests of the members.
+pub fn spawn_host(
+    args: &HostArgs,
+    status: SharedStatus,
+    keys: Receiver<Input>,
+) -> Result<(Receiver<Input>, Participants)> {
+    let address = args.listen.as_deref().unwrap_or(DEFAULT_ADDRESS);
+    let listener = TcpListener::bind(address)
+        .or_error(TomateError::Io, &render(t().team_bind_failed, &[("address", &address)]))?;
+
+    showln!(render(t().team_listening, &[("address", &address)]).dimmed());
+
+    let participants = Participants::new(Mutex::new(vec![args.name.name()]));
+    let members = Members::default();
+
+    // The keys of the host go to the timer the same way as the requests of the members
+    let (sender, receiver) = input_channel();
+
+    let keys_sender = sender.clone();
+    thread::spawn(move || {
+        for input in keys {
+            let _ = keys_sender.send(input);
+        }
+    });
+
+    {
+        let participants = participants.clone();
+        let members = members.clone();
+
+        json_lines::serve(listener, move |id, stream| {
+            handle_member(id, stream, &sender, &participants, &members)
+        });
+    }
+
+    {
+        let participants = participants.clone();
+
+        thread::spawn(move || {
+            let mut last = String::new();
+
+            loop {
+                thread::sleep(BROADCAST_INTERVAL);
+
+                let message = state(&status, &participants);
+                let line = serde_json::to_string(&message).unwrap();
+
+                if line != last {
+                    broadcast(&members, &line);
+                    last = line;
+                }
+            }
+        });
+    }
+
+    Ok((receiver, participants))
+}
+
+fn state(status: &SharedStatus, participants: &Participants) -> HostMessage {
+    HostMessage::State {
+        status: status.lock().unwrap().clone(),
+        participants: participants.lock().unwrap().clone(),
+    }
+}
+
+/// Sends a line to every member, forgetting the ones that went away or stopped reading, see
+/// [`WRITE_TIMEOUT`].
+fn broadcast(members: &Members, line: &str) {
+    members
+        .lock()
+        .unwrap()
+        .retain_mut(|(_, stream)| writeln!(stream, "{line}").is_ok());
+}
+
+/// Adds the member to the session once it says hello, forwarding its requests to the timer and telling everyone
+/// who made them, until it leaves.
+fn handle_member(
+    id: u64,
+    stream: TcpStream,
+    sender: &SyncSender<Input>,
+    participants: &Participants,
+    members: &Members,
+) -> io::Result<()> {
+    let mut name = None;
+
+    stream.set_write_timeout(Some(WRITE_TIMEOUT))?;
+
+    let read = json_lines::read_lines(stream.try_clone()?, |message| {
+        match message {
+            Ok(MemberMessage::Hello { name: hello }) if name.is_none() => {
+                participants.lock().unwrap().push(hello.clone());
+                members.lock().unwrap().push((id, stream.try_clone()?));
+                name = Some(hello);
+            }
+            Ok(MemberMessage::Control { request }) => {
+                // Members can't stop the session for everyone, only leave it
+                if let (Some(control @ (Control::Pause | Control::Resume | Control::Skip)), Some(name)) =
+                    (request.control(), &name)
+                {
+                    let _ = sender.send(Input::Control(control));
+                    let action = HostMessage::Action {
+                        name: name.clone(),
+                        request,
+                    };
+                    broadcast(members, &serde_json::to_string(&action).unwrap());
+                }
+            }
+            // Ignored, like a second hello or a line from something else
+            _ => {}
+        }
+
+        Ok(())
+    });
+
+    if let Some(name) = name {
+        let mut participants = participants.lock().unwrap();
+        // The host is never removed, even if a member has the same name
+        if let Some(index) = participants.iter().skip(1).position(|participant| *participant == name) {
+            participants.remove(index + 1);
+        }
+    }
+    members.lock().unwrap().retain(|(member, _)| *member != id);
+
+    read
+}
+
+/// What the member waits for, either a key or a line from the host, `None` once it hangs up.
+enum MemberEvent {
+    Input(Input),
+    Host(Option<HostMessage>),
+}
+
+/// Mirrors the timer of the host until it ends the session or the member leaves with `q`.
+pub fn run_join(args: JoinArgs, notifier: Chain) -> Result<()> {
+    let connect_failed = render(t().team_connect_failed, &[("address", &args.address)]);
+    let stream = TcpStream::connect(&args.address).or_error(TomateError::Io, &connect_failed)?;
+
+    let hello = MemberMessage::Hello { name: args.name.name() };
+    json_lines::write_line(&stream, &hello).or_error(TomateError::Io, &connect_failed)?;
+
+    let (sender, events) = mpsc::channel();
+
+    let host_sender = sender.clone();
+    let reader = stream.try_clone().or_error(TomateError::Io, &connect_failed)?;
+    thread::spawn(move || {
+        // Lines from a newer host that can't be parsed are skipped
+        let _ = json_lines::read_lines(reader, |message| {
+            if let Ok(message) = message {
+                let _ = host_sender.send(MemberEvent::Host(Some(message)));
+            }
+            Ok(())
+        });
+        let _ = host_sender.send(MemberEvent::Host(None));
+    });
+
+    let keys = spawn_stdin_channel();
+    thread::spawn(move || {
+        for input in keys {
+            let _ = sender.send(MemberEvent::Input(input));
+        }
+    });
+
+    let mut last = None;
+
+    for event in events {
+        match event {
+            MemberEvent::Host(Some(HostMessage::State { status, participants })) => {
+                // Only once the stage changes, not when joining in the middle of it
+                if let Some(stage) = status
+                    .stage
+                    .filter(|stage| last.as_ref().is_some_and(|last: &Status| last.stage != Some(*stage)))
+                {
+                    let message = match stage {
+                        Stage::Work => t().team_work_started,
+                        Stage::Rest => t().team_rest_started,
+                    };
+                    if notifier.notify(message).is_err() {
+                        warn(&TomateError::Io(render(t().notification_failed, &[("body", &message)])));
+                    }
+                }
+
+                draw(&status, &participants);
+                last = Some(status);
+            }
+            MemberEvent::Host(Some(HostMessage::Action { name, request })) => {
+                let template = match request {
+                    Request::Pause => t().team_paused,
+                    Request::Resume => t().team_resumed,
+                    _ => t().team_skipped,
+                };
+
+                clear_footer();
+                println!(
+                    "{CLEAR_LINE}\r  {}",
+                    render(template, &[("name", &name.color(palette().task.0))])
+                );
+            }
+            MemberEvent::Host(None) => {
+                clear_footer();
+                println!("{CLEAR_LINE}\r  {}", t().team_ended.dimmed());
+                break;
+            }
+            MemberEvent::Input(input) => {
+                let request = match input.control() {
+                    Some(Control::TogglePause) if last.as_ref().is_some_and(|last| last.paused) => Request::Resume,
+                    Some(Control::TogglePause | Control::Pause) => Request::Pause,
+                    Some(Control::Resume) => Request::Resume,
+                    Some(Control::Skip) => Request::Skip,
+                    Some(Control::Stop) => {
+                        clear_footer();
+                        println!();
+                        break;
+                    }
+                    _ => continue,
+                };
+
+                // A host that went away is noticed by the reader
+                let _ = json_lines::write_line(&stream, &MemberMessage::Control { request });
+            }
+        }
+    }
+
+    terminal::restore();
+
+    Ok(())
+}
+
+/// The timer line of the host, with everyone in the session after it and the keys below.
+fn draw(status: &Status, participants: &[String]) {
+    let Some(stage) = status.stage else {
+        return;
+    };
+
+    let time = if status.overtime_secs > 0 {
+        format!("+{}", Time::from(Duration::from_secs(status.overtime_secs)))
+    } else {
+        Time::from(Duration::from_secs(status.remaining_secs)).to_string()
+    };
+    let paused = if status.paused {
+        format!(" {}", t().paused.red())
+    } else {
+        String::new()
+    };
+    let progress = render(t().progress, &[("cycle", &status.cycle), ("cycles", &status.cycles)]);
+    let task = status.task.as_ref().map(|task| format!(": {task}")).unwrap_or_default();
+
+    let mut stdout = io::stdout();
+    let _ = write!(
+        stdout,
+        "{CLEAR_LINE}\r  {stage} {}{paused}  {}{}  {}\n{CLEAR_LINE}  {}\x1B[1A\r",
+        time.color(palette().time.0),
+        progress.dimmed(),
+        task.color(palette().task.0),
+        format!("👥 {}", participants.join(", ")).dimmed(),
+        t().team_keys_help.dimmed(),
+    );
+    let _ = stdout.flush();
+}
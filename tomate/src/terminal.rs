@@ -0,0 +1,98 @@
+use std::{
+    io::{self, Write},
+    mem::MaybeUninit,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Mutex,
+    },
+};
+
+use crate::CLEAR_LINE;
+
+/// Terminal settings from before [`enable_raw_mode`], to put back by [`disable_raw_mode`].
+static ORIGINAL: Mutex<Option<libc::termios>> = Mutex::new(None);
+static ALTERNATE_SCREEN: AtomicBool = AtomicBool::new(false);
+
+/// Delivers each key as soon as it's pressed, without echoing it.
+///
+/// Only input is touched: output processing stays on so `\n` still returns the cursor, and so does
+/// `ISIG`, so Ctrl-C still interrupts, see [`restore_on_ctrl_c`]. Does nothing if STDIN isn't a terminal.
+pub fn enable_raw_mode() {
+    let mut original = ORIGINAL.lock().unwrap();
+
+    if original.is_some() || unsafe { libc::isatty(libc::STDIN_FILENO) } != 1 {
+        return;
+    }
+
+    let mut termios = MaybeUninit::uninit();
+
+    if unsafe { libc::tcgetattr(libc::STDIN_FILENO, termios.as_mut_ptr()) } != 0 {
+        return;
+    }
+
+    let termios = unsafe { termios.assume_init() };
+    let mut raw = termios;
+    raw.c_lflag &= !(libc::ICANON | libc::ECHO);
+    raw.c_cc[libc::VMIN] = 1;
+    raw.c_cc[libc::VTIME] = 0;
+
+    if unsafe { libc::tcsetattr(libc::STDIN_FILENO, libc::TCSANOW, &raw) } == 0 {
+        *original = Some(termios);
+    }
+}
+
+/// Puts the terminal back the way it was, safe to call even if nothing was changed.
+pub fn restore() {
+    leave_alternate_screen();
+    disable_raw_mode();
+}
+
+/// Back to line editing with echo, does nothing if raw mode wasn't enabled.
+pub fn disable_raw_mode() {
+    // Also called from the Ctrl+C handler and on errors, when the terminal must be restored even if the lock
+    // was poisoned by a panic
+    let mut original = ORIGINAL.lock().unwrap_or_else(|poisoned| poisoned.into_inner());
+
+    if let Some(termios) = original.take() {
+        unsafe { libc::tcsetattr(libc::STDIN_FILENO, libc::TCSANOW, &termios) };
+    }
+}
+
+/// Switches to a screen of its own, like editors do, so the shell output is still there when leaving.
+pub fn enter_alternate_screen() {
+    if !ALTERNATE_SCREEN.swap(true, Ordering::SeqCst) {
+        print!("\x1B[?1049h");
+        let _ = io::stdout().flush();
+    }
+}
+
+/// Goes back to the main screen, showing the cursor again, does nothing if it wasn't left.
+pub fn leave_alternate_screen() {
+    if ALTERNATE_SCREEN.swap(false, Ordering::SeqCst) {
+        print!("\x1B[?25h\x1B[?1049l");
+        let _ = io::stdout().flush();
+    }
+}
+
+/// Columns and rows of the terminal, `None` if STDOUT isn't one.
+pub fn size() -> Option<(usize, usize)> {
+    let mut size = MaybeUninit::<libc::winsize>::uninit();
+
+    if unsafe { libc::ioctl(libc::STDOUT_FILENO, libc::TIOCGWINSZ, size.as_mut_ptr()) } != 0 {
+        return None;
+    }
+
+    let size = unsafe { size.assume_init() };
+    (size.ws_col > 0 && size.ws_row > 0).then_some((size.ws_col as usize, size.ws_row as usize))
+}
+
+pub fn restore_on_ctrl_c() {
+    // Only fails if a handler was already set
+    let _ = ctrlc::set_handler(|| {
+        restore();
+        // Leave the timer line, erasing the help footer below it
+        print!("\n{CLEAR_LINE}");
+        let _ = io::stdout().flush();
+        std::process::exit(130);
+    });
+}
@@ -1,7 +1,14 @@
-use std::{fmt, time::Duration};
+use std::{fmt, str::FromStr, time::Duration};
+
+use serde::Deserialize;
+
+use crate::i18n::{render, t};
+
+pub const MINUTE: Duration = Duration::from_secs(60);
 
 #[derive(Clone, Copy)]
 pub struct Time {
+    hours: u32,
     minutes: u32,
     seconds: u32,
 }
@@ -10,25 +17,180 @@ impl From<u32> for Time {
     fn from(seconds: u32) -> Self {
         Self {
             seconds: seconds % 60,
-            minutes: seconds / 60,
+            minutes: seconds / 60 % 60,
+            hours: seconds / 60 / 60,
         }
     }
 }
 
 impl From<Duration> for Time {
     fn from(duration: Duration) -> Self {
-        assert!(
-            duration < Duration::from_secs(60 * 60),
-            "Duration is bigger than an hour"
-        );
-
-        Self::from(duration.as_secs() as u32)
+        Self::from(u32::try_from(duration.as_secs()).unwrap_or(u32::MAX))
     }
 }
 
 impl fmt::Display for Time {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        let Self { minutes, seconds } = self;
-        write!(f, "{minutes:02}:{seconds:02}")
+        let Self {
+            hours,
+            minutes,
+            seconds,
+        } = self;
+
+        if *hours > 0 {
+            write!(f, "{hours}:{minutes:02}:{seconds:02}")
+        } else {
+            write!(f, "{minutes:02}:{seconds:02}")
+        }
+    }
+}
+
+/// A duration given by the user, like `1h30m`, `45s` or `2.5m`, where bare numbers are minutes.
+///
+/// Rounded to whole seconds, since that's the precision of the timer.
+#[derive(Clone, Copy, Deserialize)]
+#[serde(try_from = "DurationValue")]
+pub struct HumanDuration(pub Duration);
+
+impl FromStr for HumanDuration {
+    type Err = String;
+
+    fn from_str(text: &str) -> Result<Self, Self::Err> {
+        let invalid = || render(t().invalid_duration, &[("text", &text)]);
+
+        let mut seconds = 0.0;
+        let mut number = String::new();
+
+        for char in text.trim().chars() {
+            let unit = match char {
+                '0'..='9' | '.' => {
+                    number.push(char);
+                    continue;
+                }
+                'h' => 3600.0,
+                'm' => 60.0,
+                's' => 1.0,
+                _ => return Err(invalid()),
+            };
+
+            seconds += number.parse::<f64>().map_err(|_| invalid())? * unit;
+            number.clear();
+        }
+
+        // Trailing number without unit, only allowed alone, as minutes
+        if !number.is_empty() {
+            if seconds != 0.0 {
+                return Err(invalid());
+            }
+            seconds = number.parse::<f64>().map_err(|_| invalid())? * 60.0;
+        }
+
+        Ok(Self(Duration::from_secs(seconds.round() as u64)))
+    }
+}
+
+/// Plan and config files accept both numbers of minutes and duration strings.
+#[derive(Deserialize)]
+#[serde(untagged)]
+enum DurationValue {
+    Minutes(f64),
+    Text(String),
+}
+
+impl TryFrom<DurationValue> for HumanDuration {
+    type Error = String;
+
+    fn try_from(value: DurationValue) -> Result<Self, Self::Error> {
+        match value {
+            DurationValue::Minutes(minutes) if minutes >= 0.0 => {
+                Ok(Self(Duration::from_secs((minutes * 60.0).round() as u64)))
+            }
+            DurationValue::Minutes(minutes) => Err(render(t().invalid_duration, &[("text", &minutes)])),
+            DurationValue::Text(text) => text.parse(),
+        }
+    }
+}
+
+/// Describes a duration for messages, "25 minutos" when it's made of whole minutes, "1h30m" or "45s" otherwise.
+pub fn describe(duration: Duration) -> String {
+    let seconds = duration.as_secs();
+    let (hours, minutes, seconds) = (seconds / 3600, seconds / 60 % 60, seconds % 60);
+
+    if hours == 0 && seconds == 0 {
+        return render(t().minutes, &[("minutes", &minutes)]);
+    }
+
+    let mut text = String::new();
+
+    for (amount, unit) in [(hours, 'h'), (minutes, 'm'), (seconds, 's')] {
+        if amount > 0 {
+            text += &format!("{amount}{unit}");
+        }
+    }
+
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse(text: &str) -> Result<Duration, String> {
+        text.parse().map(|duration: HumanDuration| duration.0)
+    }
+
+    #[test]
+    fn bare_numbers_are_minutes() {
+        assert_eq!(parse("90"), Ok(90 * MINUTE));
+        assert_eq!(parse(" 25 "), Ok(25 * MINUTE));
+        assert_eq!(parse("2.5"), Ok(Duration::from_secs(150)));
+    }
+
+    #[test]
+    fn units_add_up() {
+        assert_eq!(parse("1h30m"), Ok(90 * MINUTE));
+        assert_eq!(parse("45s"), Ok(Duration::from_secs(45)));
+        assert_eq!(parse("2.5m"), Ok(Duration::from_secs(150)));
+        assert_eq!(parse("1h1m1s"), Ok(Duration::from_secs(3661)));
+    }
+
+    #[test]
+    fn rounds_to_whole_seconds() {
+        assert_eq!(parse("0.01m"), Ok(Duration::from_secs(1)));
+        assert_eq!(parse("1.4s"), Ok(Duration::from_secs(1)));
+    }
+
+    #[test]
+    fn rejects_anything_else() {
+        for text in ["1h30", "25 minutes", "1..5m", "m", "-5", "3:50"] {
+            assert_eq!(
+                parse(text),
+                Err(render(t().invalid_duration, &[("text", &text)])),
+                "{text}"
+            );
+        }
+    }
+
+    #[test]
+    fn minutes_or_text_in_files() {
+        let parse = |value| HumanDuration::try_from(value).map(|duration| duration.0);
+
+        assert_eq!(parse(DurationValue::Minutes(2.5)), Ok(Duration::from_secs(150)));
+        assert_eq!(parse(DurationValue::Text("1h".to_owned())), Ok(60 * MINUTE));
+        assert!(parse(DurationValue::Minutes(-1.0)).is_err());
+    }
+
+    #[test]
+    fn shows_hours_only_when_needed() {
+        assert_eq!(Time::from(90).to_string(), "01:30");
+        assert_eq!(Time::from(3661).to_string(), "1:01:01");
+        assert_eq!(Time::from(Duration::from_secs(25 * 60)).to_string(), "25:00");
+    }
+
+    #[test]
+    fn describes_whole_minutes_in_words() {
+        assert_eq!(describe(25 * MINUTE), render(t().minutes, &[("minutes", &25)]));
+        assert_eq!(describe(90 * MINUTE), "1h30m");
+        assert_eq!(describe(Duration::from_secs(45)), "45s");
     }
 }
@@ -0,0 +1,567 @@
+use std::{
+    cell::Cell,
+    rc::Rc,
+    sync::mpsc::{self, Receiver, Sender},
+    time::{Duration, Instant},
+};
+
+/// Where the timer gets the time from, so it can be driven by something else than the wall clock.
+pub trait Clock {
+    fn now(&self) -> Instant;
+
+    /// The real instant to wait until for the clock to reach `at`.
+    fn wait_until(&self, at: Instant) -> Instant {
+        at
+    }
+
+    /// Whether the time only passes when waited on, so what counts up without an end, like the
+    /// overtime, must wait for the user instead.
+    fn is_simulated(&self) -> bool {
+        false
+    }
+}
+
+pub struct SystemClock;
+
+impl Clock for SystemClock {
+    fn now(&self) -> Instant {
+        Instant::now()
+    }
+}
+
+/// The wall clock sped up by a factor, for demos.
+pub struct ScaledClock {
+    origin: Instant,
+    factor: f64,
+}
+
+impl ScaledClock {
+    pub fn new(factor: f64) -> Self {
+        Self {
+            origin: Instant::now(),
+            factor,
+        }
+    }
+}
+
+impl Clock for ScaledClock {
+    fn now(&self) -> Instant {
+        self.origin + self.origin.elapsed().mul_f64(self.factor)
+    }
+
+    fn wait_until(&self, at: Instant) -> Instant {
+        self.origin + at.saturating_duration_since(self.origin).div_f64(self.factor)
+    }
+}
+
+/// A clock that only moves when waited on, jumping right to the instant waited for, so a whole
+/// sprint runs as fast as it can be drawn.
+pub struct SimulatedClock {
+    now: Cell<Instant>,
+}
+
+impl Default for SimulatedClock {
+    fn default() -> Self {
+        Self {
+            now: Cell::new(Instant::now()),
+        }
+    }
+}
+
+impl Clock for SimulatedClock {
+    fn now(&self) -> Instant {
+        self.now.get()
+    }
+
+    fn wait_until(&self, at: Instant) -> Instant {
+        self.now.set(self.now.get().max(at));
+        // Only what's already waiting, like a key pressed meanwhile
+        Instant::now()
+    }
+
+    fn is_simulated(&self) -> bool {
+        true
+    }
+}
+
+/// What happened to a [`Timer`], sent to every subscriber.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum TimerEvent {
+    Paused,
+    Resumed,
+    Extended(Duration),
+    Shortened(Duration),
+    Rewound(Duration),
+    Skipped,
+    Finished,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum TimerState {
+    Running,
+    Paused,
+    Skipped,
+    Finished,
+}
+
+/// A countdown that only keeps instants, never counting ticks, so it can't drift however long it
+/// runs or however late it's polled.
+pub struct Timer {
+    clock: Rc<dyn Clock>,
+    duration: Duration,
+    state: TimerState,
+    /// Running time of the previous runs, before the last pause.
+    ran_for: Duration,
+    /// Start of the current run, `None` while paused or over.
+    running_since: Option<Instant>,
+    paused_since: Option<Instant>,
+    paused_for: Duration,
+    pauses: u32,
+    subscribers: Vec<Sender<TimerEvent>>,
+}
+
+impl Timer {
+    /// A timer already running for the given duration.
+    pub fn start(clock: Rc<dyn Clock>, duration: Duration) -> Self {
+        Self {
+            running_since: Some(clock.now()),
+            clock,
+            duration,
+            state: TimerState::Running,
+            ran_for: Duration::ZERO,
+            paused_since: None,
+            paused_for: Duration::ZERO,
+            pauses: 0,
+            subscribers: Vec::new(),
+        }
+    }
+
+    /// A timer for the given duration that waits for [`Timer::resume`] to start counting.
+    pub fn start_paused(clock: Rc<dyn Clock>, duration: Duration) -> Self {
+        Self {
+            state: TimerState::Paused,
+            running_since: None,
+            paused_since: Some(clock.now()),
+            ..Self::start(clock, duration)
+        }
+    }
+
+    /// Receives the events from now on, a subscriber that went away is just dropped.
+    pub fn subscribe(&mut self) -> Receiver<TimerEvent> {
+        let (sender, receiver) = mpsc::channel();
+        self.subscribers.push(sender);
+        receiver
+    }
+
+    fn emit(&mut self, event: TimerEvent) {
+        self.subscribers.retain(|subscriber| subscriber.send(event).is_ok());
+    }
+
+    pub fn state(&self) -> TimerState {
+        self.state
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.state == TimerState::Paused
+    }
+
+    pub fn is_over(&self) -> bool {
+        matches!(self.state, TimerState::Skipped | TimerState::Finished)
+    }
+
+    /// Time spent counting down, pauses excluded.
+    pub fn elapsed(&self) -> Duration {
+        let running = self
+            .running_since
+            .map(|since| self.clock.now().saturating_duration_since(since))
+            .unwrap_or_default();
+
+        (self.ran_for + running).min(self.duration)
+    }
+
+    pub fn remaining(&self) -> Duration {
+        self.duration - self.elapsed()
+    }
+
+    /// Like [`Timer::remaining`], rounded up so the display only reaches zero when the timer ends.
+    pub fn remaining_secs(&self) -> Duration {
+        let remaining = self.remaining();
+        let whole = Duration::from_secs(remaining.as_secs());
+
+        if remaining > whole {
+            whole + Duration::from_secs(1)
+        } else {
+            whole
+        }
+    }
+
+    /// When [`Timer::remaining_secs`] goes down next, the time to wake up for redrawing.
+    pub fn next_tick(&self) -> Instant {
+        let remaining = self.remaining();
+        let fraction = remaining - Duration::from_secs(remaining.as_secs());
+
+        let until = if fraction.is_zero() {
+            remaining.min(Duration::from_secs(1))
+        } else {
+            fraction
+        };
+
+        self.clock.now() + until
+    }
+
+    pub fn paused_for(&self) -> Duration {
+        let paused = self
+            .paused_since
+            .map(|since| self.clock.now().saturating_duration_since(since))
+            .unwrap_or_default();
+
+        self.paused_for + paused
+    }
+
+    pub fn pauses(&self) -> u32 {
+        self.pauses
+    }
+
+    /// Updates the state with the current time, emitting [`TimerEvent::Finished`] once it's done.
+    pub fn poll(&mut self) -> TimerState {
+        if self.state == TimerState::Running && self.remaining().is_zero() {
+            self.stop(TimerState::Finished);
+            self.emit(TimerEvent::Finished);
+        }
+
+        self.state
+    }
+
+    pub fn pause(&mut self) {
+        if self.poll() != TimerState::Running {
+            return;
+        }
+
+        let now = self.clock.now();
+        self.ran_for += now.saturating_duration_since(self.running_since.take().unwrap());
+        self.paused_since = Some(now);
+        self.state = TimerState::Paused;
+        self.pauses += 1;
+        self.emit(TimerEvent::Paused);
+    }
+
+    pub fn resume(&mut self) {
+        if self.state != TimerState::Paused {
+            return;
+        }
+
+        let now = self.clock.now();
+        self.paused_for += now.saturating_duration_since(self.paused_since.take().unwrap());
+        self.running_since = Some(now);
+        self.state = TimerState::Running;
+        self.emit(TimerEvent::Resumed);
+    }
+
+    pub fn toggle_pause(&mut self) {
+        if self.is_paused() {
+            self.resume();
+        } else {
+            self.pause();
+        }
+    }
+
+    pub fn skip(&mut self) {
+        if self.is_over() {
+            return;
+        }
+
+        self.stop(TimerState::Skipped);
+        self.emit(TimerEvent::Skipped);
+    }
+
+    pub fn extend(&mut self, by: Duration) {
+        if self.is_over() {
+            return;
+        }
+
+        self.duration += by;
+        self.emit(TimerEvent::Extended(by));
+    }
+
+    /// Takes time off the countdown, at most what's left, which finishes it at the next poll.
+    pub fn shorten(&mut self, by: Duration) {
+        if self.is_over() {
+            return;
+        }
+
+        let by = by.min(self.remaining());
+        self.duration -= by;
+        self.emit(TimerEvent::Shortened(by));
+    }
+
+    /// Gives back time already counted, like idle time that shouldn't count, at most what has elapsed.
+    pub fn rewind(&mut self, by: Duration) {
+        if self.is_over() {
+            return;
+        }
+
+        let elapsed = self.elapsed();
+        let by = by.min(elapsed);
+
+        self.ran_for = elapsed - by;
+        if self.running_since.is_some() {
+            self.running_since = Some(self.clock.now());
+        }
+        self.emit(TimerEvent::Rewound(by));
+    }
+
+    /// Freezes the elapsed and paused times, ending the timer.
+    fn stop(&mut self, state: TimerState) {
+        self.ran_for = self.elapsed();
+        self.paused_for = self.paused_for();
+        self.running_since = None;
+        self.paused_since = None;
+        self.state = state;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SECOND: Duration = Duration::from_secs(1);
+
+    /// Only moves when told to.
+    struct ManualClock {
+        now: Cell<Instant>,
+    }
+
+    impl ManualClock {
+        fn new() -> Rc<Self> {
+            Rc::new(Self {
+                now: Cell::new(Instant::now()),
+            })
+        }
+
+        fn advance(&self, by: Duration) {
+            self.now.set(self.now.get() + by);
+        }
+    }
+
+    impl Clock for ManualClock {
+        fn now(&self) -> Instant {
+            self.now.get()
+        }
+    }
+
+    fn received(receiver: &Receiver<TimerEvent>) -> Vec<TimerEvent> {
+        receiver.try_iter().collect()
+    }
+
+    #[test]
+    fn counts_down_and_finishes() {
+        let clock = ManualClock::new();
+        let mut timer = Timer::start(clock.clone(), 3 * SECOND);
+        let subscriber = timer.subscribe();
+
+        assert_eq!(timer.poll(), TimerState::Running);
+        assert_eq!(timer.remaining_secs(), 3 * SECOND);
+
+        clock.advance(SECOND);
+        assert_eq!(timer.poll(), TimerState::Running);
+        assert_eq!(timer.elapsed(), SECOND);
+        assert_eq!(timer.remaining_secs(), 2 * SECOND);
+
+        clock.advance(5 * SECOND);
+        assert_eq!(timer.poll(), TimerState::Finished);
+        assert_eq!(timer.elapsed(), 3 * SECOND);
+        assert!(timer.remaining().is_zero());
+        assert_eq!(received(&subscriber), [TimerEvent::Finished]);
+
+        // Finishing is only reported once
+        assert_eq!(timer.poll(), TimerState::Finished);
+        assert!(received(&subscriber).is_empty());
+    }
+
+    #[test]
+    fn pauses_and_resumes() {
+        let clock = ManualClock::new();
+        let mut timer = Timer::start(clock.clone(), 10 * SECOND);
+        let subscriber = timer.subscribe();
+
+        clock.advance(2 * SECOND);
+        timer.pause();
+        clock.advance(30 * SECOND);
+
+        assert!(timer.is_paused());
+        assert_eq!(timer.elapsed(), 2 * SECOND);
+        assert_eq!(timer.paused_for(), 30 * SECOND);
+
+        // Pausing twice doesn't count twice
+        timer.pause();
+        assert_eq!(timer.pauses(), 1);
+
+        timer.resume();
+        clock.advance(3 * SECOND);
+
+        assert_eq!(timer.poll(), TimerState::Running);
+        assert_eq!(timer.elapsed(), 5 * SECOND);
+        assert_eq!(timer.paused_for(), 30 * SECOND);
+        assert_eq!(received(&subscriber), [TimerEvent::Paused, TimerEvent::Resumed]);
+    }
+
+    #[test]
+    fn starts_paused() {
+        let clock = ManualClock::new();
+        let mut timer = Timer::start_paused(clock.clone(), 10 * SECOND);
+
+        clock.advance(4 * SECOND);
+        assert!(timer.elapsed().is_zero());
+        assert_eq!(timer.paused_for(), 4 * SECOND);
+
+        timer.toggle_pause();
+        clock.advance(SECOND);
+        assert_eq!(timer.elapsed(), SECOND);
+    }
+
+    #[test]
+    fn skipping_freezes_the_times() {
+        let clock = ManualClock::new();
+        let mut timer = Timer::start(clock.clone(), 10 * SECOND);
+        let subscriber = timer.subscribe();
+
+        clock.advance(4 * SECOND);
+        timer.skip();
+        clock.advance(4 * SECOND);
+
+        assert_eq!(timer.poll(), TimerState::Skipped);
+        assert_eq!(timer.elapsed(), 4 * SECOND);
+
+        // Nothing changes a timer that is over
+        timer.extend(SECOND);
+        timer.shorten(SECOND);
+        timer.rewind(SECOND);
+        timer.pause();
+        timer.skip();
+
+        assert_eq!(timer.elapsed(), 4 * SECOND);
+        assert_eq!(received(&subscriber), [TimerEvent::Skipped]);
+    }
+
+    #[test]
+    fn extends_and_shortens() {
+        let clock = ManualClock::new();
+        let mut timer = Timer::start(clock.clone(), 10 * SECOND);
+        let subscriber = timer.subscribe();
+
+        clock.advance(4 * SECOND);
+        timer.extend(60 * SECOND);
+        assert_eq!(timer.remaining(), 66 * SECOND);
+
+        timer.shorten(60 * SECOND);
+        assert_eq!(timer.remaining(), 6 * SECOND);
+
+        // At most what's left, finishing at the next poll
+        timer.shorten(60 * SECOND);
+        assert_eq!(timer.poll(), TimerState::Finished);
+        assert_eq!(timer.elapsed(), 4 * SECOND);
+
+        assert_eq!(
+            received(&subscriber),
+            [
+                TimerEvent::Extended(60 * SECOND),
+                TimerEvent::Shortened(60 * SECOND),
+                TimerEvent::Shortened(6 * SECOND),
+                TimerEvent::Finished,
+            ]
+        );
+    }
+
+    #[test]
+    fn rewinds_at_most_what_elapsed() {
+        let clock = ManualClock::new();
+        let mut timer = Timer::start(clock.clone(), 10 * SECOND);
+        let subscriber = timer.subscribe();
+
+        clock.advance(6 * SECOND);
+        timer.rewind(2 * SECOND);
+        assert_eq!(timer.elapsed(), 4 * SECOND);
+
+        clock.advance(SECOND);
+        assert_eq!(timer.elapsed(), 5 * SECOND);
+
+        timer.rewind(60 * SECOND);
+        assert!(timer.elapsed().is_zero());
+
+        // Also while paused, without counting the pause
+        clock.advance(3 * SECOND);
+        timer.pause();
+        timer.rewind(SECOND);
+        timer.resume();
+        assert_eq!(timer.elapsed(), 2 * SECOND);
+
+        assert_eq!(
+            received(&subscriber),
+            [
+                TimerEvent::Rewound(2 * SECOND),
+                TimerEvent::Rewound(5 * SECOND),
+                TimerEvent::Paused,
+                TimerEvent::Rewound(SECOND),
+                TimerEvent::Resumed,
+            ]
+        );
+    }
+
+    #[test]
+    fn ticks_dont_drift_when_polled_late() {
+        let clock = ManualClock::new();
+        let timer = Timer::start(clock.clone(), 25 * 60 * SECOND);
+        let started_at = clock.now();
+
+        // Waking up a bit late every time, like a loaded machine would
+        for tick in 1..=100u32 {
+            let next_tick = timer.next_tick();
+            clock.now.set(next_tick + Duration::from_millis(7));
+
+            assert_eq!(timer.remaining_secs(), (25 * 60 - tick) * SECOND);
+            // The next tick is on the whole second from the start, not a second after waking up
+            assert_eq!(timer.next_tick(), started_at + (tick + 1) * SECOND);
+        }
+    }
+
+    #[test]
+    fn ticks_on_whole_seconds_after_a_pause() {
+        let clock = ManualClock::new();
+        let mut timer = Timer::start(clock.clone(), 10 * SECOND);
+
+        clock.advance(Duration::from_millis(1500));
+        timer.pause();
+        assert_eq!(timer.remaining_secs(), 9 * SECOND);
+
+        clock.advance(Duration::from_millis(700));
+        timer.resume();
+        assert_eq!(timer.next_tick(), clock.now() + Duration::from_millis(500));
+
+        clock.advance(Duration::from_millis(500));
+        assert_eq!(timer.remaining_secs(), 8 * SECOND);
+        assert_eq!(timer.next_tick(), clock.now() + SECOND);
+    }
+
+    #[test]
+    fn every_subscriber_gets_every_event() {
+        let clock = ManualClock::new();
+        let mut timer = Timer::start(clock.clone(), 10 * SECOND);
+        let first = timer.subscribe();
+
+        timer.pause();
+
+        let second = timer.subscribe();
+        let dropped = timer.subscribe();
+        drop(dropped);
+
+        timer.resume();
+        timer.skip();
+
+        assert_eq!(
+            received(&first),
+            [TimerEvent::Paused, TimerEvent::Resumed, TimerEvent::Skipped]
+        );
+        // Only from when it subscribed
+        assert_eq!(received(&second), [TimerEvent::Resumed, TimerEvent::Skipped]);
+    }
+}
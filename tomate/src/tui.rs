@@ -0,0 +1,199 @@
+use std::io::{self, Write};
+
+use owo_colors::{AnsiColors, OwoColorize};
+
+use crate::{
+    colors::palette,
+    i18n::{render, t},
+    plan::Progress,
+    terminal,
+    time::Time,
+    Stage,
+};
+
+const PROGRESS_BAR_WIDTH: usize = 50;
+
+/// Glyphs of the big clock, each 5 columns wide and 5 rows tall.
+const DIGITS: [[&str; 5]; 10] = [
+    ["█████", "█   █", "█   █", "█   █", "█████"],
+    ["    █", "    █", "    █", "    █", "    █"],
+    ["█████", "    █", "█████", "█    ", "█████"],
+    ["█████", "    █", "█████", "    █", "█████"],
+    ["█   █", "█   █", "█████", "    █", "    █"],
+    ["█████", "█    ", "█████", "    █", "█████"],
+    ["█████", "█    ", "█████", "█   █", "█████"],
+    ["█████", "    █", "    █", "    █", "    █"],
+    ["█████", "█   █", "█████", "█   █", "█████"],
+    ["█████", "█   █", "█████", "    █", "█████"],
+];
+const COLON: [&str; 5] = ["   ", " █ ", "   ", " █ ", "   "];
+const PLUS: [&str; 5] = ["     ", "  █  ", "█████", "  █  ", "     "];
+
+/// Everything shown in the full screen mode.
+pub struct Frame<'a> {
+    pub stage: Stage,
+    pub time: Time,
+    pub paused: bool,
+    /// Whether `time` is counting up after the tomato ended.
+    pub overtime: bool,
+    /// How much of the current stage is done, from 0 to 1.
+    pub done: f64,
+    pub progress: Progress,
+    pub task: Option<&'a str>,
+    /// Everyone in the team session, when hosting one.
+    pub participants: Option<&'a str>,
+    /// Progress towards the goals, shown instead of the tomatoes completed today.
+    pub goal: Option<&'a str>,
+    pub completed_today: u32,
+}
+
+/// A line to be centered, with its width without the color escapes.
+struct Line {
+    width: usize,
+    text: String,
+}
+
+impl Line {
+    fn new(plain: &str, text: impl ToString) -> Self {
+        Self {
+            width: plain.chars().count(),
+            text: text.to_string(),
+        }
+    }
+
+    fn empty() -> Self {
+        Self::new("", "")
+    }
+}
+
+/// Clears the screen and draws the frame centered in it, with the key bindings at the bottom.
+pub fn draw(frame: &Frame) {
+    let (columns, rows) = terminal::size().unwrap_or((80, 24));
+    let color = match frame.stage {
+        Stage::Work => palette().work.0,
+        Stage::Rest => palette().rest.0,
+    };
+
+    let stage = match frame.stage {
+        Stage::Work => t().stage_work,
+        Stage::Rest => t().stage_rest,
+    };
+    let task = frame.task.map(|task| format!(": {task}")).unwrap_or_default();
+
+    let mut lines = vec![
+        Line::new(
+            &format!("{stage}{task}"),
+            format_args!("{}{}", stage.color(color), task.color(palette().task.0)),
+        ),
+        Line::empty(),
+    ];
+
+    let clock = if frame.overtime {
+        format!("+{}", frame.time)
+    } else {
+        frame.time.to_string()
+    };
+    let clock_color = if frame.paused {
+        AnsiColors::Red
+    } else if frame.overtime {
+        AnsiColors::Yellow
+    } else {
+        color
+    };
+    lines.extend(big_clock(&clock, columns, clock_color));
+
+    lines.push(Line::empty());
+    lines.push(if frame.paused {
+        Line::new(t().paused, t().paused.red())
+    } else if frame.overtime {
+        Line::new(t().overtime, t().overtime.yellow())
+    } else {
+        Line::empty()
+    });
+    lines.push(progress_bar(
+        frame.done,
+        columns.saturating_sub(10).min(PROGRESS_BAR_WIDTH),
+        color,
+    ));
+    lines.push(Line::empty());
+
+    let today = match frame.goal {
+        Some(goal) => goal.to_owned(),
+        None => render(t().completed_today, &[("count", &frame.completed_today)]),
+    };
+    let position = format!("{} · {today}", frame.progress);
+    lines.push(Line::new(&position, position.dimmed()));
+    if let Some(participants) = frame.participants {
+        lines.push(Line::new(participants, participants.dimmed()));
+    }
+
+    let top = rows.saturating_sub(lines.len()) / 2;
+
+    // Hide the cursor, it's shown again when leaving the alternate screen
+    let mut screen = String::from("\x1B[?25l\x1B[H\x1B[2J");
+
+    for (index, line) in lines.iter().enumerate() {
+        screen += &move_to(top + index, columns.saturating_sub(line.width) / 2);
+        screen += &line.text;
+    }
+
+    let keys = if frame.overtime {
+        t().overtime_help
+    } else {
+        t().keys_help
+    };
+    screen += &move_to(rows.saturating_sub(1), columns.saturating_sub(keys.chars().count()) / 2);
+    screen += &keys.dimmed().to_string();
+
+    let mut stdout = io::stdout();
+    // A terminal that went away is noticed by STDIN, there's nothing to draw on anyway
+    let _ = stdout.write_all(screen.as_bytes());
+    let _ = stdout.flush();
+}
+
+/// Clears the screen and shows the cursor again, to ask something on it.
+pub fn clear_for_prompt() {
+    print!("\x1B[H\x1B[2J\x1B[?25h");
+}
+
+/// Cursor movement to the given position, starting from 0.
+fn move_to(row: usize, column: usize) -> String {
+    format!("\x1B[{};{}H", row + 1, column + 1)
+}
+
+/// Five rows with the time in big digits, or just one if the terminal is too narrow for them.
+fn big_clock(text: &str, columns: usize, color: AnsiColors) -> Vec<Line> {
+    let rows = (0..5)
+        .map(|row| {
+            text.chars()
+                .map(|char| match char.to_digit(10) {
+                    Some(digit) => DIGITS[digit as usize][row],
+                    None if char == '+' => PLUS[row],
+                    None => COLON[row],
+                })
+                .collect::<Vec<_>>()
+                .join(" ")
+        })
+        .collect::<Vec<_>>();
+
+    if rows[0].chars().count() > columns {
+        return vec![Line::new(text, text.color(color))];
+    }
+
+    rows.iter().map(|row| Line::new(row, row.color(color))).collect()
+}
+
+fn progress_bar(done: f64, width: usize, color: AnsiColors) -> Line {
+    let filled = ((done.clamp(0.0, 1.0) * width as f64).round() as usize).min(width);
+    let percentage = format!(" {:>3}%", (done.clamp(0.0, 1.0) * 100.0).round());
+
+    let plain = format!("{}{}{percentage}", "█".repeat(filled), "░".repeat(width - filled));
+    let text = format!(
+        "{}{}{}",
+        "█".repeat(filled).color(color),
+        "░".repeat(width - filled).dimmed(),
+        percentage.dimmed(),
+    );
+
+    Line::new(&plain, text)
+}
@@ -0,0 +1,274 @@
+//! Helpers shared by the integration tests, which run the real binary in a sandbox of its own.
+
+// Each test crate uses its own subset
+#![allow(dead_code)]
+
+use std::{
+    fs::{self, File},
+    io::{self, Read},
+    ops::{Deref, DerefMut},
+    os::fd::{FromRawFd, OwnedFd},
+    process::{Child, Command, ExitStatus, Stdio},
+    ptr,
+    sync::{Arc, Mutex},
+    thread,
+    time::{Duration, Instant},
+};
+
+use tempfile::TempDir;
+
+pub const TIMEOUT: Duration = Duration::from_secs(20);
+
+/// Config, data and runtime directories of their own, with silent notifications and English messages.
+pub struct Sandbox {
+    dir: TempDir,
+}
+
+impl Sandbox {
+    pub fn new() -> Self {
+        Self::with_config("")
+    }
+
+    /// Appends `config` to the defaults of the sandbox.
+    pub fn with_config(config: &str) -> Self {
+        let dir = tempfile::tempdir().unwrap();
+
+        for name in ["config/tomate", "data", "runtime"] {
+            fs::create_dir_all(dir.path().join(name)).unwrap();
+        }
+        fs::write(
+            dir.path().join("config/tomate/config.toml"),
+            format!("lang = \"en\"\n{config}\n[notifications]\nbackends = [\"none\"]\n"),
+        )
+        .unwrap();
+
+        Self { dir }
+    }
+
+    pub fn command(&self) -> Command {
+        let mut command = Command::new(env!("CARGO_BIN_EXE_tomate"));
+        command
+            .env("XDG_CONFIG_HOME", self.dir.path().join("config"))
+            .env("XDG_DATA_HOME", self.dir.path().join("data"))
+            .env("XDG_RUNTIME_DIR", self.dir.path().join("runtime"))
+            .env("LANG", "en_US.UTF-8")
+            .env_remove("LC_ALL")
+            .env_remove("LC_MESSAGES");
+        command
+    }
+
+    pub fn history(&self) -> String {
+        fs::read_to_string(self.dir.path().join("data/tomate/history.jsonl")).unwrap_or_default()
+    }
+
+    /// Replaces the history with the given lines of JSON.
+    pub fn set_history(&self, history: &str) {
+        fs::create_dir_all(self.dir.path().join("data/tomate")).unwrap();
+        fs::write(self.dir.path().join("data/tomate/history.jsonl"), history).unwrap();
+    }
+
+    pub fn checkpoint(&self) -> String {
+        fs::read_to_string(self.dir.path().join("data/tomate/checkpoint.json")).unwrap_or_default()
+    }
+}
+
+/// Everything a child wrote so far, collected by a thread of its own.
+#[derive(Clone, Default)]
+pub struct Output(Arc<Mutex<String>>);
+
+impl Output {
+    pub fn collect(mut reader: impl Read + Send + 'static) -> Self {
+        let output = Self::default();
+        let text = output.0.clone();
+
+        thread::spawn(move || {
+            let mut buffer = [0; 4096];
+            while let Ok(read @ 1..) = reader.read(&mut buffer) {
+                text.lock().unwrap().push_str(&String::from_utf8_lossy(&buffer[..read]));
+            }
+        });
+
+        output
+    }
+
+    /// Without colors and with every carriage return as a new line.
+    pub fn text(&self) -> String {
+        strip_escapes(&self.0.lock().unwrap()).replace('\r', "\n")
+    }
+
+    /// What's left on a terminal after drawing everything so far, without the lines redrawn over.
+    pub fn screen(&self) -> String {
+        screen(&self.0.lock().unwrap())
+    }
+
+    /// Waits for `needle` to be written, panicking with the output so far after [`TIMEOUT`].
+    #[track_caller]
+    pub fn wait_for(&self, needle: &str) {
+        let started_at = Instant::now();
+
+        while !self.text().contains(needle) {
+            if started_at.elapsed() > TIMEOUT {
+                panic!("timed out waiting for {needle:?}, got:\n{}", self.text());
+            }
+            thread::sleep(Duration::from_millis(20));
+        }
+    }
+}
+
+/// Removes the CSI sequences, like colors and cursor movements.
+pub fn strip_escapes(text: &str) -> String {
+    let mut stripped = String::with_capacity(text.len());
+    let mut chars = text.chars();
+
+    while let Some(char) = chars.next() {
+        if char != '\x1B' {
+            stripped.push(char);
+            continue;
+        }
+
+        if chars.next() == Some('[') {
+            for char in chars.by_ref() {
+                if char.is_ascii_alphabetic() {
+                    break;
+                }
+            }
+        }
+    }
+
+    stripped
+}
+
+/// Replays what the line display uses, moving the cursor, clearing lines and writing over them. Other
+/// escape sequences, like colors, are left out.
+pub fn screen(text: &str) -> String {
+    let mut lines: Vec<Vec<char>> = vec![Vec::new()];
+    let (mut row, mut column) = (0, 0);
+    let mut chars = text.chars();
+
+    while let Some(char) = chars.next() {
+        match char {
+            '\r' => column = 0,
+            '\n' => {
+                row += 1;
+                if row == lines.len() {
+                    lines.push(Vec::new());
+                }
+            }
+            '\x1B' => {
+                if chars.next() != Some('[') {
+                    continue;
+                }
+                let mut parameter = String::new();
+                for char in chars.by_ref() {
+                    match char {
+                        'A' => row -= parameter.parse().unwrap_or(1).min(row),
+                        'K' if parameter == "2" => lines[row].clear(),
+                        'K' => lines[row].truncate(column),
+                        _ if char.is_ascii_alphabetic() => {}
+                        _ => {
+                            parameter.push(char);
+                            continue;
+                        }
+                    }
+                    break;
+                }
+            }
+            _ if char.is_control() => {}
+            _ => {
+                let line = &mut lines[row];
+                if column < line.len() {
+                    line[column] = char;
+                } else {
+                    line.resize(column, ' ');
+                    line.push(char);
+                }
+                column += 1;
+            }
+        }
+    }
+
+    let mut screen: Vec<_> = lines
+        .iter()
+        .map(|line| line.iter().collect::<String>().trim_end().to_owned())
+        .collect();
+    while screen.last().is_some_and(String::is_empty) {
+        screen.pop();
+    }
+
+    screen.join("\n")
+}
+
+/// A child that is killed when dropped, so a failed test doesn't leave it running.
+pub struct Process(Child);
+
+impl Deref for Process {
+    type Target = Child;
+
+    fn deref(&self) -> &Child {
+        &self.0
+    }
+}
+
+impl DerefMut for Process {
+    fn deref_mut(&mut self) -> &mut Child {
+        &mut self.0
+    }
+}
+
+impl Drop for Process {
+    fn drop(&mut self) {
+        let _ = self.0.kill();
+        let _ = self.0.wait();
+    }
+}
+
+/// Runs `command` with piped STDIN and STDOUT, STDERR is left alone.
+pub fn spawn(command: &mut Command) -> (Process, Output) {
+    let mut child = command.stdin(Stdio::piped()).stdout(Stdio::piped()).spawn().unwrap();
+    let output = Output::collect(child.stdout.take().unwrap());
+
+    (Process(child), output)
+}
+
+/// Runs `command` in a pseudo-terminal of 80 by 24, which is its STDIN, STDOUT and STDERR. Keys are written
+/// to the returned end.
+pub fn spawn_in_terminal(command: &mut Command) -> (Process, File, Output) {
+    let (mut controller, mut terminal) = (-1, -1);
+    let size = libc::winsize {
+        ws_row: 24,
+        ws_col: 80,
+        ws_xpixel: 0,
+        ws_ypixel: 0,
+    };
+
+    let opened = unsafe { libc::openpty(&mut controller, &mut terminal, ptr::null_mut(), ptr::null(), &size) };
+    assert_eq!(opened, 0, "{}", io::Error::last_os_error());
+    let (controller, terminal) = unsafe { (File::from_raw_fd(controller), OwnedFd::from_raw_fd(terminal)) };
+
+    let child = command
+        .stdin(Stdio::from(terminal.try_clone().unwrap()))
+        .stdout(Stdio::from(terminal.try_clone().unwrap()))
+        .stderr(Stdio::from(terminal))
+        .spawn()
+        .unwrap();
+    let output = Output::collect(controller.try_clone().unwrap());
+
+    (Process(child), controller, output)
+}
+
+/// Waits for the child to exit, killing it after [`TIMEOUT`].
+#[track_caller]
+pub fn wait_exit(child: &mut Child) -> ExitStatus {
+    let started_at = Instant::now();
+
+    loop {
+        if let Some(status) = child.try_wait().unwrap() {
+            return status;
+        }
+        if started_at.elapsed() > TIMEOUT {
+            let _ = child.kill();
+            panic!("timed out waiting for the process to exit");
+        }
+        thread::sleep(Duration::from_millis(20));
+    }
+}
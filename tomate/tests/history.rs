@@ -0,0 +1,28 @@
+//! `tomate history` over a history written by hand.
+
+mod common;
+
+use common::{strip_escapes, Sandbox};
+
+#[test]
+fn shows_the_idle_time_and_the_overtime() {
+    let sandbox = Sandbox::new();
+    sandbox.set_history(concat!(
+        r#"{"stage":"work","tomato":1,"started_at":"2026-10-18T10:00:00Z","ended_at":"2026-10-18T10:27:00Z","#,
+        r#""planned_secs":1500,"actual_secs":1380,"paused_secs":60,"pauses":1,"idle_secs":120,"overtime_secs":125,"#,
+        r#""skipped":false}"#,
+        "\n",
+        r#"{"stage":"rest","tomato":1,"started_at":"2026-10-18T10:27:00Z","ended_at":"2026-10-18T10:32:00Z","#,
+        r#""planned_secs":300,"actual_secs":300,"paused_secs":0,"pauses":0,"skipped":false}"#,
+        "\n",
+    ));
+
+    let output = sandbox.command().env("TZ", "UTC").arg("history").output().unwrap();
+    assert!(output.status.success());
+
+    assert_eq!(
+        strip_escapes(&String::from_utf8(output.stdout).unwrap()),
+        "  2026-10-18 10:00 → 10:27  [Work] 1  23:00 of 25:00  1 pause(s), 01:00  02:00 idle  02:05 of overtime\n  \
+         2026-10-18 10:27 → 10:32  [Rest] 1  05:00 of 05:00\n"
+    );
+}
@@ -0,0 +1,71 @@
+//! Sprints killed halfway and continued with `--resume`, on the real clock since nothing else is recorded.
+
+mod common;
+
+use std::{io::Write, thread, time::Duration};
+
+use common::{spawn_in_terminal, wait_exit, Sandbox};
+
+#[test]
+fn resumes_the_overtime() {
+    let sandbox = Sandbox::new();
+
+    let (mut tomate, _keys, output) = spawn_in_terminal(sandbox.command().args(["1s", "1m", "-c", "1", "--overtime"]));
+    output.wait_for("+00:01 overtime");
+    tomate.kill().unwrap();
+    tomate.wait().unwrap();
+
+    assert!(
+        sandbox.checkpoint().contains("\"overtime_secs\":"),
+        "{}",
+        sandbox.checkpoint()
+    );
+    assert_eq!(sandbox.history(), "");
+    thread::sleep(Duration::from_secs(1));
+
+    // The time offline counts as overtime too
+    let (mut tomate, mut keys, output) = spawn_in_terminal(sandbox.command().args(["--overtime", "--resume", "count"]));
+    output.wait_for("Resuming the overtime of tomato 1/1");
+    thread::sleep(Duration::from_millis(300));
+    keys.write_all(b"q").unwrap();
+    assert!(wait_exit(&mut tomate).success());
+
+    let history = sandbox.history();
+    assert_eq!(history.lines().count(), 1, "{history}");
+    let record: serde_json::Value = serde_json::from_str(&history).unwrap();
+    assert!(record["overtime_secs"].as_u64().unwrap() >= 2, "{history}");
+    assert_eq!(sandbox.checkpoint(), "");
+}
+
+#[test]
+fn resumes_the_next_stage_without_recording_the_last_one_again() {
+    let sandbox = Sandbox::new();
+
+    let (mut tomate, mut keys, output) =
+        spawn_in_terminal(sandbox.command().args(["--ask-task", "1s", "1s", "-c", "2"]));
+    output.wait_for("Task for this tomato");
+    keys.write_all(b"\n").unwrap();
+
+    // Asking for the task of the second tomato, the first rest being in the history
+    while output.text().matches("Task for this tomato").count() < 2 {
+        thread::sleep(Duration::from_millis(20));
+    }
+    assert_eq!(sandbox.history().lines().count(), 2);
+    tomate.kill().unwrap();
+    tomate.wait().unwrap();
+
+    let checkpoint = sandbox.checkpoint();
+    assert!(checkpoint.contains("\"upcoming\":true"), "{checkpoint}");
+    assert!(checkpoint.contains("\"tomato\":2"), "{checkpoint}");
+
+    let (mut tomate, mut keys, output) =
+        spawn_in_terminal(sandbox.command().args(["--ask-task", "--resume", "discard"]));
+    output.wait_for("Resuming tomato 2/2");
+    output.wait_for("Task for this tomato");
+    keys.write_all(b"\nq").unwrap();
+    assert!(wait_exit(&mut tomate).success());
+
+    let history = sandbox.history();
+    assert_eq!(history.matches("\"stage\":\"rest\"").count(), 1, "{history}");
+    assert_eq!(history.matches("\"tomato\":2").count(), 1, "{history}");
+}
@@ -0,0 +1,82 @@
+//! Whole sprints with `--dry-run`, in a pseudo-terminal like they'd run for the user.
+
+mod common;
+
+use std::{fs, io::Write, thread, time::Duration};
+
+use common::{spawn_in_terminal, wait_exit, Sandbox};
+
+/// User and system time used by the process so far, in clock ticks.
+fn cpu_ticks(pid: u32) -> u64 {
+    let stat = fs::read_to_string(format!("/proc/{pid}/stat")).unwrap();
+    // Fields after the name, which can have spaces, starting with the state
+    let fields: Vec<_> = stat.rsplit_once(')').unwrap().1.split_whitespace().collect();
+
+    fields[11].parse::<u64>().unwrap() + fields[12].parse::<u64>().unwrap()
+}
+
+#[test]
+fn runs_a_whole_sprint_at_once() {
+    let sandbox = Sandbox::new();
+
+    let (mut tomate, _keys, output) = spawn_in_terminal(sandbox.command().args(["25", "5", "-c", "4", "--dry-run"]));
+    assert!(wait_exit(&mut tomate).success());
+    output.wait_for("[Rest] 00:01  cycle 4/4");
+
+    assert_eq!(
+        output.screen(),
+        "\
+Simulated time, nothing goes to the history.
+[1/4] Started a tomato of 25 minutes! (cycle 1/4)
+  [Work] 00:01  cycle 1/4
+  [Yay!] Tomato 1/4 done! Your reward: 🍅
+  [Rest] 00:01  cycle 1/4
+[2/4] Started a tomato of 25 minutes! (cycle 2/4)
+  [Work] 00:01  cycle 2/4
+  [Yay!] Tomato 2/4 done! Your reward: 🥗
+  [Rest] 00:01  cycle 2/4
+[3/4] Started a tomato of 25 minutes! (cycle 3/4)
+  [Work] 00:01  cycle 3/4
+  [Yay!] Tomato 3/4 done! Your reward: 🍝
+  [Rest] 00:01  cycle 3/4
+[4/4] Started a tomato of 25 minutes! (cycle 4/4)
+  [Work] 00:01  cycle 4/4
+  [Yay!] Tomato 4/4 done! Your reward: 🍕
+  [Rest] 00:01  cycle 4/4"
+    );
+    assert_eq!(sandbox.history(), "");
+}
+
+#[test]
+fn overtime_waits_for_a_key_without_spinning() {
+    let sandbox = Sandbox::new();
+
+    let (mut tomate, mut keys, output) =
+        spawn_in_terminal(
+            sandbox
+                .command()
+                .args(["25", "5", "-c", "1", "--dry-run", "--overtime"]),
+        );
+    output.wait_for("+00:00 overtime");
+
+    let before = cpu_ticks(tomate.id());
+    thread::sleep(Duration::from_secs(1));
+    let used = cpu_ticks(tomate.id()) - before;
+    // A tick is usually 10 ms, spinning would take about 100
+    assert!(used < 20, "used {used} ticks while waiting");
+
+    keys.write_all(b" ").unwrap();
+    assert!(wait_exit(&mut tomate).success());
+    output.wait_for("[Rest] 00:01  cycle 1/1");
+    assert_eq!(sandbox.history(), "");
+}
+
+#[test]
+fn speed_and_dry_run_conflict() {
+    let sandbox = Sandbox::new();
+
+    let output = sandbox.command().args(["--speed", "60", "--dry-run"]).output().unwrap();
+
+    assert_eq!(output.status.code(), Some(2));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("cannot be used with"));
+}
@@ -0,0 +1,83 @@
+//! A host and two members, each in a process of its own, talking over loopback.
+
+mod common;
+
+use std::{io::Write, net::TcpListener, process::Child};
+
+use common::{spawn, wait_exit, Sandbox};
+
+fn free_address() -> String {
+    let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+    listener.local_addr().unwrap().to_string()
+}
+
+fn press(child: &mut Child, keys: &str) {
+    let stdin = child.stdin.as_mut().unwrap();
+    stdin.write_all(keys.as_bytes()).unwrap();
+    stdin.flush().unwrap();
+}
+
+#[test]
+fn members_share_the_timer_of_the_host() {
+    let sandbox = Sandbox::new();
+    let address = free_address();
+
+    let (mut host, host_output) = spawn(
+        sandbox
+            .command()
+            .args(["host", "1", "1", "-c", "1"])
+            .args(["--listen", &address, "--name", "ana"]),
+    );
+    host_output.wait_for(&format!("Session open at {address}"));
+
+    let (mut bia, bia_output) = spawn(sandbox.command().args(["join", &address, "--name", "bia"]));
+    bia_output.wait_for("👥 ana, bia");
+
+    let (mut carla, carla_output) = spawn(sandbox.command().args(["join", &address, "--name", "carla"]));
+    carla_output.wait_for("👥 ana, bia, carla");
+    host_output.wait_for("👥 ana, bia, carla");
+
+    // Everyone is told about the controls of the others
+    press(&mut bia, " ");
+    carla_output.wait_for("bia paused");
+    host_output.wait_for("(Paused)");
+    // Space toggles what the member last saw
+    carla_output.wait_for("(Paused)");
+
+    press(&mut carla, " ");
+    bia_output.wait_for("carla resumed");
+
+    // Leaving only takes the member out
+    press(&mut bia, "q");
+    assert!(wait_exit(&mut bia).success());
+    carla_output.wait_for("👥 ana, carla\n");
+
+    press(&mut carla, "s");
+    carla_output.wait_for("carla skipped");
+    carla_output.wait_for("[Rest]");
+    host_output.wait_for("[Rest]");
+
+    // Only the host ends the session, for everyone
+    press(&mut host, "q");
+    assert!(wait_exit(&mut host).success());
+    carla_output.wait_for("The session is over.");
+    assert!(wait_exit(&mut carla).success());
+
+    // Only the host records, the skipped work and the rest it quit
+    let history = sandbox.history();
+    let lines: Vec<_> = history.lines().collect();
+    assert_eq!(lines.len(), 2, "{history}");
+    assert!(
+        lines[0].contains(r#""stage":"work""#) && lines[0].contains(r#""skipped":true"#),
+        "{history}"
+    );
+    assert!(lines[1].contains(r#""stage":"rest""#), "{history}");
+}
+
+#[test]
+fn listens_only_on_loopback_by_default() {
+    let sandbox = Sandbox::new();
+
+    let help = sandbox.command().args(["host", "--help"]).output().unwrap();
+    assert!(String::from_utf8_lossy(&help.stdout).contains("`127.0.0.1:7878` by default"));
+}